@@ -0,0 +1,18 @@
+use std::fs;
+
+use cosmwasm_schema::generate_api;
+use bidding_contract::factory::msg::{ExecMsg, InstantiateMsg, QueryMsg};
+
+// `write_api!` clears the schema directory, so the factory schema is written next to the bidding one by hand
+fn main() {
+    let api = generate_api! {
+        name: "auction_factory",
+        instantiate: InstantiateMsg,
+        execute: ExecMsg,
+        query: QueryMsg,
+    };
+
+    let path = std::env::current_dir().unwrap().join("schema").join("auction_factory.json");
+    fs::write(&path, api.render().to_string().unwrap() + "\n").unwrap();
+    println!("Exported the full API as {}", path.to_str().unwrap());
+}
@@ -1,10 +1,11 @@
 use cosmwasm_schema::write_api;
-use bidding_contract::msg::{ExecMsg, InstantiateMsg, QueryMsg};
+use bidding_contract::msg::{ExecMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
\ No newline at end of file
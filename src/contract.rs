@@ -1,267 +1,1909 @@
-use cosmwasm_std::{Coin, DepsMut, MessageInfo, Response, StdResult, Uint128};
+use cosmwasm_std::{to_json_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use semver::Version;
 
-use crate::state::{IS_OPEN, BID_ASSET, COMMISSION, COMMODITY, OWNER};
+use crate::error::ContractError;
+use crate::factory::msg::ExecMsg as RegistryMsg;
+use crate::msg::{AuctionMode, BidIncrement, Commission, CommissionPolicy, InstantiateMsg, MigrateMsg};
+use crate::state::{Bid, AUCTION_COUNT, BID_TOKEN, BIDS, CANCELLATION, CLAIMED, COMMISSION, COMMISSION_POLICY, COMMITMENTS, END_TIME, HIGEST_BID, IS_OPEN, MIN_INCREMENT, MODE, OWNER, PROXY_MAX, REGISTRY, RUNNER_UP, SECOND_PRICE, SOLD, START_TIME};
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub fn instantiate(deps: DepsMut, info: MessageInfo, commodity: String, asset: Coin, commission: Uint128, owner: Option<String>) -> StdResult<Response> {
+pub const REGISTRY_REPLY_ID: u64 = 1;
+pub const NFT_TRANSFER_REPLY_ID: u64 = 2;
+
+pub fn instantiate(mut deps: DepsMut, env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    AUCTION_COUNT.save(deps.storage, &0)?;
+
+    // The registry registers the first auction itself once the contract is instantiated
+    let registry = msg.registry.clone();
+    let resp = exec::create_auction(deps.branch(), env, info, msg)?;
+
+    if let Some(registry) = registry {
+        REGISTRY.save(deps.storage, &deps.api.addr_validate(&registry)?)?;
+    }
+
+    Ok(resp)
+}
+
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // Only failures are replied to, the registry falls behind instead of blocking the auction
+        REGISTRY_REPLY_ID => Ok(Response::new()
+            .add_attribute("action", "update_registry")
+            .add_attribute("error", msg.result.into_result().err().unwrap_or_default())),
+        // The NFT stays with the contract, bids can still be settled and retracted
+        NFT_TRANSFER_REPLY_ID => Ok(Response::new()
+            .add_attribute("action", "transfer_nft")
+            .add_attribute("error", msg.result.into_result().err().unwrap_or_default())),
+        id => Err(StdError::generic_err(format!("Unknown reply id {id}")).into()),
+    }
+}
+
+pub fn migrate(mut deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = cw2::get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContract { contract: stored.contract });
+    }
+
+    let version: Version = stored.version.parse()?;
+    if version > CONTRACT_VERSION.parse()? {
+        return Err(ContractError::Downgrade { version: stored.version });
+    }
+
+    // Each step upgrades the storage of the versions before it
+    if version < Version::new(0, 2, 0) {
+        migrate::v0_2_0(deps.branch(), &env)?;
+    }
+
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    let owner_addr = match owner {
-        Some(owner) => deps.api.addr_validate(&owner)?,
-        None => info.sender,
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+fn min_next_bid(storage: &dyn Storage, auction_id: u64) -> StdResult<Uint128> {
+    match HIGEST_BID.may_load(storage, auction_id)? {
+        Some(hb) => next_bid(storage, auction_id, hb.bid.amount),
+        None => Ok(Uint128::new(0)),
+    }
+}
+
+/// Lowest bid outbidding the amount by the minimum increment.
+fn next_bid(storage: &dyn Storage, auction_id: u64, amount: Uint128) -> StdResult<Uint128> {
+    let increment = match MIN_INCREMENT.may_load(storage, auction_id)? {
+        Some(BidIncrement::Absolute { amount }) => amount,
+        Some(BidIncrement::Percentage { percent }) => amount.multiply_ratio(percent, 100u128),
+        None => Uint128::new(0),
+    };
+
+    // Equal bids never outbid the highest bid
+    Ok(amount.checked_add(increment.max(Uint128::new(1)))?)
+}
+
+/// Highest bidder bidding by proxy, its maximum is above the current price.
+fn proxy_leader(storage: &dyn Storage, auction_id: u64) -> StdResult<Option<Bid>> {
+    if !PROXY_MAX.has(storage, auction_id) {
+        return Ok(None);
+    }
+
+    HIGEST_BID.may_load(storage, auction_id)
+}
+
+/// Highest bidder whose commission is held until the price it pays is known, a proxy or any second-price leader.
+fn commission_holder(storage: &dyn Storage, auction_id: u64) -> StdResult<Option<Addr>> {
+    if !PROXY_MAX.has(storage, auction_id) && !SECOND_PRICE.load(storage, auction_id)? {
+        return Ok(None);
+    }
+
+    Ok(HIGEST_BID.may_load(storage, auction_id)?.map(|hb| hb.address))
+}
+
+/// Price paid by the highest bidder, in a second-price auction the runner-up bid or the given reserve price.
+fn clearing_price(storage: &dyn Storage, auction_id: u64, highest: &Bid, reserve: Uint128) -> StdResult<Uint128> {
+    if !SECOND_PRICE.load(storage, auction_id)? {
+        return Ok(highest.bid.amount);
+    }
+
+    let runner_up = RUNNER_UP.may_load(storage, auction_id)?.map_or(Uint128::new(0), |b| b.bid.amount);
+
+    Ok(runner_up.max(reserve).min(highest.bid.amount))
+}
+
+/// Commission charged on a deposit, tiers are chosen by the total bid including the deposit.
+fn commission(storage: &dyn Storage, auction_id: u64, bid: Uint128, deposit: Uint128) -> StdResult<Uint128> {
+    let amount = match COMMISSION.load(storage, auction_id)? {
+        Commission::Rate { rate } => deposit.mul_floor(rate),
+        Commission::Flat { amount } => amount,
+        Commission::Tiered { tiers } => {
+            let rate = tiers
+                .iter()
+                .rev()
+                .find(|tier| bid >= tier.min_bid)
+                .map_or(Decimal::zero(), |tier| tier.rate);
+
+            deposit.mul_floor(rate)
+        }
+    };
+
+    Ok(amount)
+}
+
+fn validate_commission(commission: &Commission) -> Result<(), ContractError> {
+    let valid = match commission {
+        Commission::Rate { rate } => *rate <= Decimal::one(),
+        Commission::Flat { .. } => true,
+        Commission::Tiered { tiers } => {
+            !tiers.is_empty()
+                && tiers.iter().all(|tier| tier.rate <= Decimal::one())
+                && tiers.windows(2).all(|pair| pair[0].min_bid < pair[1].min_bid)
+        }
+    };
+
+    if !valid {
+        return Err(ContractError::InvalidCommission {});
+    }
+
+    Ok(())
+}
+
+/// Funds the address can retract from a closed auction, the error tells why there are none.
+fn claimable(storage: &dyn Storage, auction_id: u64, address: &Addr) -> Result<Coin, ContractError> {
+    if IS_OPEN.load(storage, auction_id)? {
+        return Err(ContractError::BiddingOpen {});
+    }
+
+    // Without a sale the highest bidder can retract as well, otherwise only the surplus over the clearing price
+    let is_buyer = SOLD.load(storage, auction_id)?
+        && HIGEST_BID.may_load(storage, auction_id)?.is_some_and(|winner| *address == winner.address);
+    let no_surplus = BIDS.may_load(storage, (auction_id, address.clone()))?.is_none_or(|deposit| deposit.net.is_zero());
+    if is_buyer && no_surplus && !SECOND_PRICE.load(storage, auction_id)? {
+        return Err(ContractError::RetractByWinner {});
+    }
+
+    // Deposit of a sealed bid that was never revealed
+    if let Some(commitment) = COMMITMENTS.may_load(storage, (auction_id, address.clone()))? {
+        // Nothing is forfeited when the auction is cancelled
+        if let AuctionMode::Sealed { forfeit_unrevealed: true, .. } = MODE.load(storage, auction_id)? {
+            if !CANCELLATION.has(storage, auction_id) {
+                return Err(ContractError::DepositForfeited {});
+            }
+        }
+
+        return Ok(commitment.deposit);
+    }
+
+    let deposit = BIDS.may_load(storage, (auction_id, address.clone()))?.ok_or(ContractError::NoBid {})?;
+    let mut refund = deposit.net;
+
+    if refunds_commission(storage, auction_id, is_buyer)? {
+        refund += deposit.commission;
+    }
+
+    let claimed = CLAIMED.may_load(storage, (auction_id, address.clone()))?.unwrap_or_default();
+    if claimed >= refund {
+        // A winner without surplus never had anything to claim
+        return match claimed.is_zero() {
+            true => Err(ContractError::NoBid {}),
+            false => Err(ContractError::AlreadyClaimed {}),
+        };
+    }
+
+    Ok(Coin {
+        denom: deposit.gross.denom,
+        amount: refund - claimed,
+    })
+}
+
+/// Whether bidders get their commission back, escrowed commission is only charged to the winner.
+fn refunds_commission(storage: &dyn Storage, auction_id: u64, is_buyer: bool) -> StdResult<bool> {
+    let escrowed = matches!(COMMISSION_POLICY.load(storage, auction_id)?, CommissionPolicy::Escrowed {});
+    if let Some(cancellation) = CANCELLATION.may_load(storage, auction_id)? {
+        return Ok(cancellation.refund_commission || escrowed);
+    }
+
+    Ok(!is_buyer && escrowed)
+}
+
+/// Tells the registry about the owner and status of the auction.
+fn update_registry(storage: &dyn Storage, auction_id: u64) -> StdResult<Option<SubMsg>> {
+    let registry = match REGISTRY.may_load(storage)? {
+        Some(registry) => registry,
+        None => return Ok(None),
+    };
+
+    let update = WasmMsg::Execute {
+        contract_addr: registry.to_string(),
+        msg: to_json_binary(&RegistryMsg::UpdateAuction {
+            auction_id,
+            owner: OWNER.may_load(storage, auction_id)?.map(|owner| owner.to_string()),
+            is_open: IS_OPEN.load(storage, auction_id)?,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Some(SubMsg::reply_on_error(update, REGISTRY_REPLY_ID)))
+}
+
+/// Transfers the bid asset, with a cw20 transfer when bids are paid in a cw20 token.
+fn send_asset(storage: &dyn Storage, auction_id: u64, to_address: String, amount: Coin) -> StdResult<CosmosMsg> {
+    match BID_TOKEN.may_load(storage, auction_id)? {
+        Some(token) => Ok(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: to_address,
+                amount: amount.amount,
+            })?,
+            funds: vec![],
+        }.into()),
+        None => Ok(BankMsg::Send {
+            to_address,
+            amount: vec![amount],
+        }.into()),
+    }
+}
+
+/// Time after which anyone can close the auction, sealed bids are revealed after the end time.
+fn closing_time(storage: &dyn Storage, auction_id: u64) -> StdResult<Option<Timestamp>> {
+    let end_time = END_TIME.may_load(storage, auction_id)?;
+
+    match MODE.load(storage, auction_id)? {
+        AuctionMode::Sealed { reveal_period, .. } => Ok(end_time.map(|end_time| end_time.plus_seconds(reveal_period))),
+        AuctionMode::Open {} | AuctionMode::Dutch { .. } => Ok(end_time),
+    }
+}
+
+/// Price of a Dutch auction at the given time, declining from the start price to the floor price.
+fn dutch_price(storage: &dyn Storage, auction_id: u64, time: Timestamp) -> StdResult<Option<Uint128>> {
+    let (start_price, floor_price, decline_period, step) = match MODE.load(storage, auction_id)? {
+        AuctionMode::Dutch { start_price, floor_price, decline_period, step } => (start_price, floor_price, decline_period, step),
+        _ => return Ok(None),
     };
 
-    OWNER.save(deps.storage, &owner_addr)?;
-    COMMODITY.save(deps.storage, &commodity)?;
-    BID_ASSET.save(deps.storage, &asset)?;
-    COMMISSION.save(deps.storage, &commission)?;
-    IS_OPEN.save(deps.storage, &true)?;
-    // No initial BIDS
-    // No initial HIGEST_BID
+    let start_time = START_TIME.load(storage, auction_id)?;
+    let mut elapsed = time.seconds().saturating_sub(start_time.seconds());
+    if let Some(step) = step {
+        elapsed = elapsed / step * step;
+    }
+    let elapsed = elapsed.min(decline_period);
 
-    Ok(Response::new())
+    Ok(Some(start_price - (start_price - floor_price).multiply_ratio(elapsed, decline_period)))
 }
 
 pub mod query {
     use cosmwasm_std::Addr;
     use cosmwasm_std::Coin;
     use cosmwasm_std::Deps;
+    use cosmwasm_std::Env;
+    use cosmwasm_std::Order;
+    use cosmwasm_std::StdError;
     use cosmwasm_std::StdResult;
     use cosmwasm_std::Uint128;
 
-    use crate::msg::{AuctionResp, BidResp};
-    use crate::state::BIDS;
-    use crate::state::{BID_ASSET, COMMISSION, COMMODITY, HIGEST_BID, IS_OPEN};
+    use crate::error::ContractError;
+    use cw_storage_plus::Bound;
+
+    use crate::msg::{AllBidsResp, AuctionResp, AuctionsResp, BidResp, BidEventResp, BidHistoryResp, CommitmentResp, FeeResp, FeesResp, OwnershipResp, SettlementResp, SortOrder, WinnerResp};
+    use crate::state::{BidEvent, Deposit, BIDS, BID_COUNT, BID_HISTORY, CANCELLATION, ESCROWED, PAUSED, PAUSER, COMMITMENTS, FEES, HISTORY};
+    use crate::state::{AUCTION_COUNT, BID_ASSET, OWNER, PROXY_MAX, BUY_NOW, COMMISSION, COMMISSION_POLICY, COMMODITY, END_TIME, EXTENSIONS, CLEARING_PRICE, HIDDEN_RESERVE, HIGEST_BID, IS_OPEN, MIN_INCREMENT, MODE, NFT, PENDING_OWNER, SECOND_PRICE, SETTLEMENT, SOFT_CLOSE, SOLD, START_TIME};
+    use crate::state::BID_TOKEN;
 
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
 
-    pub fn auction(deps: Deps) -> StdResult<AuctionResp> {
-        let commodity = COMMODITY.load(deps.storage)?;
-        let bid_asset = BID_ASSET.load(deps.storage)?;
-        let commission = COMMISSION.load(deps.storage)?;
-        let is_open = IS_OPEN.load(deps.storage)?;
+    pub fn auction(deps: Deps, auction_id: u64) -> StdResult<AuctionResp> {
+        let owner = OWNER.may_load(deps.storage, auction_id)?;
+        let commodity = COMMODITY.load(deps.storage, auction_id)?;
+        let mut bid_asset = BID_ASSET.load(deps.storage, auction_id)?;
+        let hidden_reserve = HIDDEN_RESERVE.load(deps.storage, auction_id)?;
+
+        // The reserve price is met by the current price of the highest bid, or by a proxy bidding up to it on close
+        let reserve_met = match HIGEST_BID.may_load(deps.storage, auction_id)? {
+            Some(hb) => hb.bid.amount >= bid_asset.amount || PROXY_MAX.may_load(deps.storage, auction_id)?.is_some_and(|max_bid| max_bid >= bid_asset.amount),
+            None => false,
+        };
+
+        if hidden_reserve {
+            bid_asset.amount = Uint128::new(0);
+        }
+
+        let commission = COMMISSION.load(deps.storage, auction_id)?;
+        let commission_policy = COMMISSION_POLICY.load(deps.storage, auction_id)?;
+        let is_open = IS_OPEN.load(deps.storage, auction_id)?;
+        let start_time = START_TIME.load(deps.storage, auction_id)?;
+        let end_time = END_TIME.may_load(deps.storage, auction_id)?;
+        let soft_close = SOFT_CLOSE.may_load(deps.storage, auction_id)?;
+        let extensions = EXTENSIONS.load(deps.storage, auction_id)?;
+        let min_increment = MIN_INCREMENT.may_load(deps.storage, auction_id)?;
+        let buy_now = BUY_NOW.may_load(deps.storage, auction_id)?;
+        let mode = MODE.load(deps.storage, auction_id)?;
+        let second_price = SECOND_PRICE.load(deps.storage, auction_id)?;
+        let cw20 = BID_TOKEN.has(deps.storage, auction_id);
+        let nft = NFT.may_load(deps.storage, auction_id)?;
+        let cancellation = CANCELLATION.may_load(deps.storage, auction_id)?;
+        let paused = PAUSED.load(deps.storage, auction_id)?;
+        let pauser = PAUSER.may_load(deps.storage, auction_id)?;
 
         Ok(AuctionResp {
+            auction_id,
+            owner: owner.map(|owner| owner.to_string()),
             commodity,
             bid_asset,
             commission,
+            commission_policy,
             is_open,
+            start_time,
+            end_time,
+            soft_close,
+            extensions,
+            hidden_reserve,
+            reserve_met,
+            min_increment,
+            buy_now,
+            mode,
+            second_price,
+            cw20,
+            nft,
+            cancellation,
+            paused,
+            pauser: pauser.map(|pauser| pauser.to_string()),
         })
     }
 
-    pub fn bids(deps: Deps, address: String) -> StdResult<Coin> {
-        let addr = Addr::unchecked(address);    // Ignoring to check address format as it's not critical for the contract
-        let bid = BIDS.may_load(deps.storage, addr)?;
+    pub fn auctions(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<AuctionsResp> {
+        let count = AUCTION_COUNT.load(deps.storage)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map_or(1, |id| id + 1);
+
+        let auctions = (start..=count)
+            .take(limit)
+            .map(|auction_id| auction(deps, auction_id))
+            .collect::<StdResult<_>>()?;
+
+        Ok(AuctionsResp { auctions })
+    }
 
-        if let Some(bid) = bid {
-            return Ok(bid);
+    /// Current price of the proxy leading an open auction, its maximum is kept hidden.
+    fn proxy_price(deps: Deps, auction_id: u64) -> StdResult<Option<(Addr, Uint128)>> {
+        if !IS_OPEN.load(deps.storage, auction_id)? {
+            return Ok(None);
         }
-        
-        let bid_asset = BID_ASSET.load(deps.storage)?;
 
-        Ok(Coin { 
-            denom: bid_asset.denom, 
-            amount: Uint128::new(0),
-        })
+        Ok(super::proxy_leader(deps.storage, auction_id)?.map(|hb| (hb.address, hb.bid.amount)))
     }
 
-    pub fn highest_bid(deps: Deps) -> StdResult<BidResp> {
-        let higest_bid = HIGEST_BID.may_load(deps.storage)?;
+    fn bid_resp(deps: Deps, auction_id: u64, address: Addr) -> StdResult<BidResp> {
+        let denom = BID_ASSET.load(deps.storage, auction_id)?.denom;
+        let mut deposit = BIDS.may_load(deps.storage, (auction_id, address.clone()))?.unwrap_or(Deposit {
+            gross: Coin {
+                denom: denom.clone(),
+                amount: Uint128::new(0),
+            },
+            commission: Uint128::new(0),
+            net: Uint128::new(0),
+        });
 
-        if let Some(hb) = higest_bid {
-            return Ok(BidResp {
-                address: hb.address.to_string(),
-                bid: hb.bid,
-            })
+        // The proxy leader is reported at the current price
+        if let Some((_, price)) = proxy_price(deps, auction_id)?.filter(|(leader, _)| *leader == address) {
+            deposit.commission = deposit.commission.multiply_ratio(price, deposit.gross.amount);
+            deposit.net = price - deposit.commission;
+            deposit.gross.amount = price;
         }
 
-        let bid_asset = BID_ASSET.load(deps.storage)?;
-
         Ok(BidResp {
-            address: "".to_string(),
-            bid: Coin { 
-                denom: bid_asset.denom, 
-                amount: Uint128::new(0),
+            address: address.to_string(),
+            bid: deposit.gross,
+            commission: Coin {
+                denom: denom.clone(),
+                amount: deposit.commission,
+            },
+            net: Coin {
+                denom,
+                amount: deposit.net,
+            },
+        })
+    }
+
+    pub fn bids(deps: Deps, auction_id: u64, address: String) -> StdResult<BidResp> {
+        let addr = Addr::unchecked(address);    // Ignoring to check address format as it's not critical for the contract
+
+        bid_resp(deps, auction_id, addr)
+    }
+
+    pub fn all_bids(deps: Deps, auction_id: u64, start_after: Option<String>, limit: Option<u32>, order: Option<SortOrder>) -> StdResult<AllBidsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let order = order.map_or(Order::Descending, Order::from);
+
+        let start = match start_after {
+            Some(address) => {
+                let addr = Addr::unchecked(address);
+                let deposit = BIDS.load(deps.storage, (auction_id, addr.clone()))?;
+
+                Some(Bound::exclusive((deposit.gross.amount.u128(), (auction_id, addr))))
             }
+            None => None,
+        };
+        let (min, max) = match order {
+            Order::Ascending => (start, None),
+            Order::Descending => (None, start),
+        };
+
+        let bids = BIDS
+            .idx
+            .amount
+            .sub_prefix(auction_id)
+            .range(deps.storage, min, max, order)
+            .take(limit)
+            .map(|item| item.and_then(|((_, address), _)| bid_resp(deps, auction_id, address)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(AllBidsResp {
+            bids,
+            count: BID_COUNT.load(deps.storage, auction_id)?,
+            escrowed: Coin {
+                denom: BID_ASSET.load(deps.storage, auction_id)?.denom,
+                amount: ESCROWED.load(deps.storage, auction_id)?,
+            },
         })
     }
 
-    pub fn  winner(deps: Deps) -> StdResult<BidResp> {
-        let is_open = IS_OPEN.load(deps.storage)?;
-        
-        if is_open {
-            let bid = HIGEST_BID.may_load(deps.storage)?;
+    pub fn highest_bid(deps: Deps, auction_id: u64) -> StdResult<BidResp> {
+        match HIGEST_BID.may_load(deps.storage, auction_id)? {
+            // The current price, a proxy bidder may have deposited more
+            Some(hb) => Ok(BidResp {
+                bid: hb.bid,
+                ..bid_resp(deps, auction_id, hb.address)?
+            }),
+            None => bid_resp(deps, auction_id, Addr::unchecked("")),
+        }
+    }
+
+    pub fn winner(deps: Deps, auction_id: u64) -> StdResult<WinnerResp> {
+        let is_open = IS_OPEN.load(deps.storage, auction_id)?;
+        let sold = !is_open && SOLD.load(deps.storage, auction_id)?;
+
+        // The highest bidder leads while open and has won once sold
+        if is_open || sold {
+            if let Some(hb) = HIGEST_BID.may_load(deps.storage, auction_id)? {
+                let clearing_price = match sold {
+                    true => CLEARING_PRICE.load(deps.storage, auction_id)?,
+                    false => {
+                        // A hidden reserve price is only applied on close
+                        let reserve = match HIDDEN_RESERVE.load(deps.storage, auction_id)? {
+                            true => Uint128::new(0),
+                            false => BID_ASSET.load(deps.storage, auction_id)?.amount,
+                        };
+
+                        super::clearing_price(deps.storage, auction_id, &hb, reserve)?
+                    }
+                };
 
-            if let Some(hb) = bid {
-                return Ok(BidResp {
+                return Ok(WinnerResp {
                     address: hb.address.to_string(),
+                    clearing_price: Coin {
+                        denom: hb.bid.denom.clone(),
+                        amount: clearing_price,
+                    },
                     bid: hb.bid,
+                    unsold: false,
                 })
             }
         }
 
-        let bid_asset = BID_ASSET.load(deps.storage)?;
-                
-        Ok(BidResp {
+        let bid_asset = BID_ASSET.load(deps.storage, auction_id)?;
+
+        Ok(WinnerResp {
             address: "".to_string(),
-            bid: Coin { 
-                denom: bid_asset.denom, 
+            bid: Coin {
+                denom: bid_asset.denom.clone(),
                 amount: Uint128::new(0),
-            }
+            },
+            clearing_price: Coin {
+                denom: bid_asset.denom,
+                amount: Uint128::new(0),
+            },
+            unsold: !is_open,
         })
     }
 
-}
+    pub fn settlement(deps: Deps, auction_id: u64) -> StdResult<SettlementResp> {
+        let settlement = match SETTLEMENT.may_load(deps.storage, auction_id)? {
+            Some(settlement) => settlement,
+            None => return Err(StdError::generic_err("Auction is not settled")),
+        };
+        let denom = BID_ASSET.load(deps.storage, auction_id)?.denom;
+        let coin = |amount| Coin {
+            denom: denom.clone(),
+            amount,
+        };
 
-pub mod exec {
-    use cosmwasm_std::{BankMsg, Coin, DepsMut, MessageInfo, Response, Uint128};
+        Ok(SettlementResp {
+            winner: settlement.winner.map(|winner| winner.to_string()),
+            clearing_price: coin(settlement.clearing_price),
+            commission: coin(settlement.commission),
+            proceeds: coin(settlement.proceeds),
+            height: settlement.height,
+            time: settlement.time,
+            outcome: settlement.outcome,
+        })
+    }
 
-    use crate::error::ContractError;
-    use crate::state::{Bid, BID_ASSET, BIDS, COMMISSION, HIGEST_BID, IS_OPEN, OWNER};
+    pub fn min_next_bid(deps: Deps, auction_id: u64) -> StdResult<Coin> {
+        let bid_asset = BID_ASSET.load(deps.storage, auction_id)?;
 
-    pub fn bid(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-        let mut resp = Response::new();
+        Ok(Coin {
+            denom: bid_asset.denom,
+            amount: super::min_next_bid(deps.storage, auction_id)?,
+        })
+    }
 
-        let is_open = IS_OPEN.load(deps.storage)?;
-        if !is_open {
-            return Err(ContractError::BiddingClosed {});
-        }
+    pub fn current_price(deps: Deps, env: Env, auction_id: u64) -> StdResult<Coin> {
+        let bid_asset = BID_ASSET.load(deps.storage, auction_id)?;
 
-        let owner = OWNER.load(deps.storage)?;
-        if info.sender == owner {
-            return Err(ContractError::BiddingByOwner {});
+        match super::dutch_price(deps.storage, auction_id, env.block.time)? {
+            Some(price) => Ok(Coin {
+                denom: bid_asset.denom,
+                amount: price,
+            }),
+            None => Err(StdError::generic_err("Not a Dutch auction")),
         }
+    }
 
-        let asset = BID_ASSET.load(deps.storage)?;
-        
-        match info.funds.iter().find(|c| c.denom == asset.denom) {
-            Some(funds) => {
-                let com_rate = COMMISSION.load(deps.storage)?;
-                let com_amount = funds.amount * com_rate / Uint128::new(100);
+    pub fn claimable(deps: Deps, auction_id: u64, address: String) -> StdResult<Coin> {
+        let addr = Addr::unchecked(address);
 
-                let bid = BIDS.may_load(deps.storage, info.sender.clone())?;
-                let amount = bid.map_or(funds.amount - com_amount, |b| b.amount + funds.amount - com_amount);
+        match super::claimable(deps.storage, auction_id, &addr) {
+            Ok(claim) => Ok(claim),
+            Err(ContractError::Std(err)) => Err(err),
+            Err(_) => Ok(Coin {
+                denom: BID_ASSET.load(deps.storage, auction_id)?.denom,
+                amount: Uint128::new(0),
+            }),
+        }
+    }
 
-                let h_bid_amount = HIGEST_BID.may_load(deps.storage)?.map(|b| b.bid.amount).unwrap_or(Uint128::new(0));
+    pub fn fees(deps: Deps, auction_id: u64) -> StdResult<FeesResp> {
+        let denom = BID_ASSET.load(deps.storage, auction_id)?.denom;
 
-                if amount < h_bid_amount {
-                    return Err(ContractError::BidToLow { higest_bid: h_bid_amount.to_string() });
-                }
-                BIDS.save(deps.storage, info.sender.clone(), &Coin {
-                    denom: funds.denom.clone(),
+        let fees = FEES
+            .prefix(auction_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(address, amount)| FeeResp {
+                address: address.to_string(),
+                amount: Coin {
+                    denom: denom.clone(),
                     amount,
-                })?;
+                },
+            }))
+            .collect::<StdResult<_>>()?;
 
-                // Saving the highest bid without the commission deduction
-                HIGEST_BID.save(deps.storage, &Bid {
-                    address: info.sender.clone(),
-                    bid: Coin {
-                        denom: funds.denom.clone(),
-                        amount: funds.amount,
-                    }
-                })?;
+        Ok(FeesResp { fees })
+    }
 
-                // Send commission to owner
-                let bank_msg = BankMsg::Send {
-                    to_address: owner.to_string(),
-                    amount: vec![Coin {
-                        denom: funds.denom.clone(),
-                        amount: com_amount,
-                    }],
-                };
-                
-                resp = resp
-                .add_message(bank_msg)
-                .add_attribute("action", "bid")
-                .add_attribute("sender", info.sender.as_str())
-                .add_attribute("commission", com_amount.to_string());
+    fn event_resp(event_id: u64, address: &Addr, mut event: BidEvent, proxy: &Option<(Addr, Uint128)>) -> BidEventResp {
+        // Bids of the proxy leader are capped at the current price
+        if let Some((_, price)) = proxy.as_ref().filter(|(leader, _)| leader == address) {
+            let gross = event.gross.amount.min(*price);
+            event.commission = event.commission.multiply_ratio(gross, event.gross.amount);
+            event.gross.amount = gross;
+            event.total.amount = event.total.amount.min(*price);
+        }
 
-                Ok(resp)
-            },
-            None => Err(ContractError::InvalidDenomination {denom: asset.denom}),
+        BidEventResp {
+            event_id,
+            address: address.to_string(),
+            height: event.height,
+            time: event.time,
+            bid: event.gross,
+            commission: event.commission,
+            total: event.total,
         }
     }
 
-    pub fn close(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-        let owner = OWNER.load(deps.storage)?;
-        let mut resp = Response::new();
+    pub fn bid_history(deps: Deps, auction_id: u64, address: String, start_after: Option<u64>, limit: Option<u32>) -> StdResult<BidHistoryResp> {
+        let addr = Addr::unchecked(address);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let proxy = proxy_price(deps, auction_id)?;
 
-        if info.sender != owner {
-            return Err(ContractError::Unauthorized {
-                owner: owner.to_string(),
+        let events = BID_HISTORY
+            .prefix((auction_id, addr.clone()))
+            .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(event_id, event)| event_resp(event_id, &addr, event, &proxy)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(BidHistoryResp { events })
+    }
+
+    pub fn history(deps: Deps, auction_id: u64, start_after: Option<u64>, limit: Option<u32>) -> StdResult<BidHistoryResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let proxy = proxy_price(deps, auction_id)?;
+
+        let events = HISTORY
+            .prefix(auction_id)
+            .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (event_id, address) = item?;
+                let event = BID_HISTORY.load(deps.storage, (auction_id, address.clone(), event_id))?;
+
+                Ok(event_resp(event_id, &address, event, &proxy))
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(BidHistoryResp { events })
+    }
+
+    pub fn ownership(deps: Deps, auction_id: u64) -> StdResult<OwnershipResp> {
+        let owner = OWNER.may_load(deps.storage, auction_id)?;
+        let pending = PENDING_OWNER.may_load(deps.storage, auction_id)?;
+
+        Ok(OwnershipResp {
+            owner: owner.map(|owner| owner.to_string()),
+            pending_owner: pending.as_ref().map(|pending| pending.address.to_string()),
+            pending_expiry: pending.map(|pending| pending.expiry),
+        })
+    }
+
+    pub fn commitment(deps: Deps, auction_id: u64, address: String) -> StdResult<CommitmentResp> {
+        let addr = Addr::unchecked(address);
+
+        if let Some(commitment) = COMMITMENTS.may_load(deps.storage, (auction_id, addr))? {
+            return Ok(CommitmentResp {
+                commitment: Some(commitment.commitment),
+                deposit: commitment.deposit,
             });
         }
 
-        let is_open = IS_OPEN.load(deps.storage)?;
-        if !is_open {
-            return Err(ContractError::BiddingClosed {});
+        let bid_asset = BID_ASSET.load(deps.storage, auction_id)?;
+
+        Ok(CommitmentResp {
+            commitment: None,
+            deposit: Coin {
+                denom: bid_asset.denom,
+                amount: Uint128::new(0),
+            },
+        })
+    }
+
+}
+
+pub mod exec {
+    use cosmwasm_std::{from_json, to_json_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, SubMsg, Uint128, WasmMsg};
+    use cw20::Cw20ReceiveMsg;
+    use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg, OwnerOfResponse};
+    use cw_utils::Expiration;
+
+    use crate::error::ContractError;
+    use crate::msg::{sealed_bid_commitment, AuctionMode, BidIncrement, Cancellation, CommissionPolicy, InstantiateMsg, Nft, Outcome, ReceiveMsg};
+    use crate::state::{Bid, BidEvent, Commitment, Deposit, FeeShare, PendingOwner, Settlement, AUCTION_COUNT, BID_ASSET, BID_COUNT, BID_HISTORY, BID_TOKEN, BIDS, BUY_NOW, CANCELLATION, CLAIMED, COMMISSION, COMMISSION_POLICY, COMMITMENTS, COMMODITY, CLEARING_PRICE, END_TIME, ESCROWED, EXTENSIONS, FEES, FEE_RECIPIENTS, HIDDEN_RESERVE, HIGEST_BID, HISTORY, IS_OPEN, MIN_INCREMENT, MODE, NFT, OWNER, PAUSED, PAUSER, PENDING_OWNER, HELD_COMMISSION, PROXY_MAX, RUNNER_UP, SECOND_PRICE, SETTLEMENT, SOFT_CLOSE, SOLD, START_TIME, UNREVEALED};
+
+    pub fn create_auction(deps: DepsMut, env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+        let auction_id = AUCTION_COUNT.load(deps.storage)? + 1;
+
+        let owner_addr = match msg.owner {
+            Some(owner) => deps.api.addr_validate(&owner)?,
+            None => info.sender.clone(),
+        };
+
+        let start_time = msg.start_time.unwrap_or(env.block.time);
+        if let Some(end_time) = msg.end_time {
+            if end_time <= start_time || end_time <= env.block.time {
+                return Err(ContractError::InvalidTimeWindow {});
+            }
+            END_TIME.save(deps.storage, auction_id, &end_time)?;
         }
 
-        IS_OPEN.save(deps.storage, &false)?;
-        
-        let winner = HIGEST_BID.may_load(deps.storage)?;
-        match winner {
-            Some(winner) => {
-                let funds = BIDS.load(deps.storage, winner.address.clone()).unwrap();
+        let mode = msg.mode.unwrap_or(AuctionMode::Open {});
+        match mode {
+            AuctionMode::Open {} => {}
+            AuctionMode::Sealed { reveal_period, .. } => {
+                if msg.end_time.is_none() || reveal_period == 0 || msg.soft_close.is_some() || msg.buy_now.is_some() {
+                    return Err(ContractError::InvalidSealedAuction {});
+                }
+                UNREVEALED.save(deps.storage, auction_id, &Uint128::new(0))?;
+            }
+            AuctionMode::Dutch { start_price, floor_price, decline_period, step } => {
+                // The floor price takes the place of the reserve price
+                if start_price <= floor_price
+                    || decline_period == 0
+                    || step == Some(0)
+                    || !msg.bid_asset.amount.is_zero()
+                    || msg.soft_close.is_some()
+                    || msg.buy_now.is_some()
+                    || msg.min_increment.is_some()
+                    || msg.second_price
+                {
+                    return Err(ContractError::InvalidDutchAuction {});
+                }
+            }
+        }
+        MODE.save(deps.storage, auction_id, &mode)?;
 
-                let bank_msg = BankMsg::Send {
-                    to_address: owner.to_string(),
-                    amount: vec![funds],
-                };
+        if let Some(soft_close) = msg.soft_close {
+            if msg.end_time.is_none() || soft_close.window == 0 || soft_close.extension == 0 {
+                return Err(ContractError::InvalidSoftClose {});
+            }
+            SOFT_CLOSE.save(deps.storage, auction_id, &soft_close)?;
+        }
 
-                resp = resp
-                .add_message(bank_msg)
-                .add_attribute("winner", winner.address.as_str());
+        OWNER.save(deps.storage, auction_id, &owner_addr)?;
+        COMMODITY.save(deps.storage, auction_id, &msg.commodity)?;
+        BID_ASSET.save(deps.storage, auction_id, &msg.bid_asset)?;
+        if msg.cw20 {
+            let token = deps.api.addr_validate(&msg.bid_asset.denom)?;
+            BID_TOKEN.save(deps.storage, auction_id, &token)?;
+        }
+        HIDDEN_RESERVE.save(deps.storage, auction_id, &msg.hidden_reserve)?;
+        SECOND_PRICE.save(deps.storage, auction_id, &msg.second_price)?;
+        super::validate_commission(&msg.commission)?;
+        COMMISSION.save(deps.storage, auction_id, &msg.commission)?;
+        COMMISSION_POLICY.save(deps.storage, auction_id, &msg.commission_policy.unwrap_or(CommissionPolicy::Upfront {}))?;
+        if let Some(recipients) = msg.fee_recipients {
+            let total: Decimal = recipients.iter().map(|recipient| recipient.share).sum();
+            if recipients.is_empty() || total != Decimal::one() {
+                return Err(ContractError::InvalidFeeRecipients {});
             }
-            None => {
-                resp = resp.add_attribute("winner", "None");
+
+            let recipients = recipients
+                .into_iter()
+                .map(|recipient| -> StdResult<_> {
+                    Ok(FeeShare {
+                        address: deps.api.addr_validate(&recipient.address)?,
+                        share: recipient.share,
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            FEE_RECIPIENTS.save(deps.storage, auction_id, &recipients)?;
+        }
+        if let Some(min_increment) = msg.min_increment {
+            if matches!(min_increment, BidIncrement::Percentage { percent } if percent > Uint128::new(100)) {
+                return Err(ContractError::InvalidIncrement {});
+            }
+            MIN_INCREMENT.save(deps.storage, auction_id, &min_increment)?;
+        }
+        if let Some(buy_now) = msg.buy_now {
+            // The buy-now price would be undercut by the runner-up bid
+            if buy_now.is_zero() || buy_now < msg.bid_asset.amount || msg.second_price {
+                return Err(ContractError::InvalidBuyNow {});
             }
+            BUY_NOW.save(deps.storage, auction_id, &buy_now)?;
+        }
+        if let Some(pauser) = msg.pauser {
+            PAUSER.save(deps.storage, auction_id, &deps.api.addr_validate(&pauser)?)?;
         }
+        IS_OPEN.save(deps.storage, auction_id, &true)?;
+        PAUSED.save(deps.storage, auction_id, &false)?;
+        START_TIME.save(deps.storage, auction_id, &start_time)?;
+        EXTENSIONS.save(deps.storage, auction_id, &0)?;
+        BID_COUNT.save(deps.storage, auction_id, &0)?;
+        ESCROWED.save(deps.storage, auction_id, &Uint128::new(0))?;
+        // No initial BIDS
+        // No initial HIGEST_BID
+        // No initial RUNNER_UP
 
-        resp = resp
-        .add_attribute("action", "close")
-        .add_attribute("sender", info.sender.as_str())
-        .add_attribute("bidding", "closed");
+        AUCTION_COUNT.save(deps.storage, &auction_id)?;
 
-        Ok(resp)
+        Ok(Response::new()
+            .add_submessages(super::update_registry(deps.storage, auction_id)?)
+            .add_attribute("action", "create_auction")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("auction_id", auction_id.to_string()))
     }
 
-    pub fn retract(deps: DepsMut, info: MessageInfo, receiver: Option<String>) -> Result<Response, ContractError> {
-        let mut resp = Response::new();
+    pub fn receive_nft(mut deps: DepsMut, env: Env, info: MessageInfo, msg: Cw721ReceiveMsg) -> Result<Response, ContractError> {
+        let auction: InstantiateMsg = from_json(&msg.msg)?;
+        let owner = deps.api.addr_validate(&msg.sender)?;
 
-        if IS_OPEN.load(deps.storage)? {
-            return Err(ContractError::BiddingOpen {});
+        // Anyone can send the message, only an NFT actually transferred to the contract is auctioned
+        let nft: OwnerOfResponse = deps.querier.query_wasm_smart(&info.sender, &Cw721QueryMsg::OwnerOf {
+            token_id: msg.token_id.clone(),
+            include_expired: None,
+        })?;
+        if nft.owner != env.contract.address {
+            return Err(ContractError::NftNotHeld {});
         }
 
-        let winner =  HIGEST_BID.may_load(deps.storage)?;
-        if let Some(winner) = winner {
-            if info.sender == winner.address {
-                return Err(ContractError::RetractByWinner {});
-            }
+        // The NFT contract is the sender of the message, the auction belongs to the previous holder
+        let resp = create_auction(deps.branch(), env, info.clone(), InstantiateMsg {
+            owner: Some(owner.to_string()),
+            ..auction
+        })?;
+
+        let auction_id = AUCTION_COUNT.load(deps.storage)?;
+        NFT.save(deps.storage, auction_id, &Nft {
+            contract: info.sender.clone(),
+            token_id: msg.token_id.clone(),
+        })?;
+
+        Ok(resp
+            .add_attribute("nft_contract", info.sender.as_str())
+            .add_attribute("token_id", msg.token_id))
+    }
+
+    /// A failing NFT contract never blocks closing the auction.
+    fn transfer_nft(nft: Nft, recipient: &Addr) -> StdResult<SubMsg> {
+        let transfer = WasmMsg::Execute {
+            contract_addr: nft.contract.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: recipient.to_string(),
+                token_id: nft.token_id,
+            })?,
+            funds: vec![],
+        };
+
+        Ok(SubMsg::reply_on_error(transfer, super::NFT_TRANSFER_REPLY_ID))
+    }
+
+    /// Adds the funds to the deposit of the bidder, returns the updated deposit and the commission charged.
+    fn add_deposit(storage: &mut dyn Storage, block: &BlockInfo, auction_id: u64, bidder: &Addr, funds: &Coin) -> Result<(Deposit, Uint128), ContractError> {
+        let previous = BIDS.may_load(storage, (auction_id, bidder.clone()))?;
+        if previous.is_none() {
+            BID_COUNT.update(storage, auction_id, |count| -> StdResult<_> { Ok(count.unwrap_or_default() + 1) })?;
         }
+        ESCROWED.update(storage, auction_id, |escrowed| -> StdResult<_> { Ok(escrowed.unwrap_or_default() + funds.amount) })?;
 
-        let addr = receiver.unwrap_or(info.sender.to_string());
-        match BIDS.may_load(deps.storage, info.sender)? {
-            Some(bid) => {
+        let mut deposit = previous.unwrap_or(Deposit {
+            gross: Coin {
+                denom: funds.denom.clone(),
+                amount: Uint128::new(0),
+            },
+            commission: Uint128::new(0),
+            net: Uint128::new(0),
+        });
+        deposit.gross.amount += funds.amount;
 
-                let bank_msg = BankMsg::Send {
-                    to_address: addr.clone(),
-                    amount: vec![bid],
-                };
+        // A flat fee can exceed small bids
+        let com_amount = super::commission(storage, auction_id, deposit.gross.amount, funds.amount)?;
+        if com_amount > funds.amount {
+            return Err(ContractError::BidBelowCommission { commission: com_amount.to_string() });
+        }
+        deposit.commission += com_amount;
+        deposit.net += funds.amount - com_amount;
 
-                resp = resp
-                .add_message(bank_msg)
-            }
-            None => {
-                return Err(ContractError::NoBid {});
-            }
+        BIDS.save(storage, (auction_id, bidder.clone()), &deposit)?;
+
+        // Top-ups are recorded as separate bids
+        let event_id = HISTORY
+            .prefix(auction_id)
+            .keys(storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map_or(1, |id| id + 1);
+        HISTORY.save(storage, (auction_id, event_id), bidder)?;
+        BID_HISTORY.save(storage, (auction_id, bidder.clone(), event_id), &BidEvent {
+            height: block.height,
+            time: block.time,
+            gross: funds.clone(),
+            commission: com_amount,
+            total: deposit.gross.clone(),
+        })?;
+
+        Ok((deposit, com_amount))
+    }
+
+    /// Pays the commission of a bid right away, unless it is escrowed until settlement.
+    fn charge_commission(storage: &mut dyn Storage, auction_id: u64, commission: Coin) -> StdResult<Vec<CosmosMsg>> {
+        match COMMISSION_POLICY.load(storage, auction_id)? {
+            CommissionPolicy::Upfront {} => pay_commission(storage, auction_id, commission),
+            CommissionPolicy::Escrowed {} => Ok(vec![]),
         }
+    }
 
-        resp = resp
-        .add_attribute("action", "retract")
-        .add_attribute("sender", addr);
+    /// Holds the commission of the proxy or second-price leader until the price is known, returns the commission owed now.
+    /// A leader that is outbid, or a proxy that bids outright, owes its held commission in full.
+    fn hold_commission(storage: &mut dyn Storage, auction_id: u64, previous: Option<Addr>, bidder: &Addr, commission: Uint128) -> StdResult<Uint128> {
+        // Escrowed commission is held anyway
+        if matches!(COMMISSION_POLICY.load(storage, auction_id)?, CommissionPolicy::Escrowed {}) {
+            return Ok(commission);
+        }
 
-        Ok(resp)
+        let leader = super::commission_holder(storage, auction_id)?;
+        let mut held = HELD_COMMISSION.may_load(storage, auction_id)?.unwrap_or_default();
+        let mut owed = Uint128::new(0);
+
+        if previous != leader {
+            owed += held;
+            held = Uint128::new(0);
+        }
+        match leader.as_ref() == Some(bidder) {
+            true => held += commission,
+            false => owed += commission,
+        }
+
+        match held.is_zero() {
+            true => HELD_COMMISSION.remove(storage, auction_id),
+            false => HELD_COMMISSION.save(storage, auction_id, &held)?,
+        }
+
+        Ok(owed)
+    }
+
+    /// Charges the commission of the highest bid on the price it pays, the commission on the rest of the bid is refunded.
+    fn settle_commission(storage: &mut dyn Storage, auction_id: u64, deposit: &mut Deposit, price: Uint128, sold: bool) -> StdResult<Vec<CosmosMsg>> {
+        let held = HELD_COMMISSION.may_load(storage, auction_id)?.unwrap_or_default();
+        HELD_COMMISSION.remove(storage, auction_id);
+
+        let held = match COMMISSION_POLICY.load(storage, auction_id)? {
+            // Without a sale escrowed commission goes back to the bidders
+            CommissionPolicy::Escrowed {} if !sold => return Ok(vec![]),
+            CommissionPolicy::Escrowed {} => deposit.commission,
+            CommissionPolicy::Upfront {} => held,
+        };
+
+        let paid = deposit.commission - held;
+        let due = deposit.commission.multiply_ratio(price, deposit.gross.amount).max(paid);
+        deposit.net += deposit.commission - due;
+        deposit.commission = due;
+
+        pay_commission(storage, auction_id, Coin {
+            denom: deposit.gross.denom.clone(),
+            amount: due - paid,
+        })
+    }
+
+    /// Splits the commission between the fee recipients, the rounding remainder goes to the first one.
+    fn pay_commission(storage: &mut dyn Storage, auction_id: u64, commission: Coin) -> StdResult<Vec<CosmosMsg>> {
+        // Bank rejects empty transfers
+        if commission.amount.is_zero() {
+            return Ok(vec![]);
+        }
+        ESCROWED.update(storage, auction_id, |escrowed| -> StdResult<_> { Ok(escrowed.unwrap_or_default() - commission.amount) })?;
+
+        let recipients = match FEE_RECIPIENTS.may_load(storage, auction_id)? {
+            Some(recipients) => recipients,
+            None => vec![FeeShare {
+                address: OWNER.load(storage, auction_id)?,
+                share: Decimal::one(),
+            }],
+        };
+
+        let mut fees: Vec<(Addr, Uint128)> = recipients
+            .into_iter()
+            .map(|recipient| (recipient.address, commission.amount.mul_floor(recipient.share)))
+            .collect();
+        let paid: Uint128 = fees.iter().map(|(_, fee)| *fee).sum();
+        fees[0].1 += commission.amount - paid;
+
+        let mut msgs = vec![];
+        for (address, fee) in fees {
+            if fee.is_zero() {
+                continue;
+            }
+
+            FEES.update(storage, (auction_id, address.clone()), |fees| -> StdResult<_> { Ok(fees.unwrap_or_default() + fee) })?;
+            msgs.push(super::send_asset(storage, auction_id, address.to_string(), Coin {
+                denom: commission.denom.clone(),
+                amount: fee,
+            })?);
+        }
+
+        Ok(msgs)
+    }
+
+    /// Checks that the sender can place a bid at the current block time.
+    fn ensure_bidding(storage: &dyn Storage, auction_id: u64, env: &Env, sender: &Addr) -> Result<(), ContractError> {
+        let is_open = IS_OPEN.load(storage, auction_id)?;
+        if !is_open {
+            return Err(ContractError::BiddingClosed {});
+        }
+
+        let start_time = START_TIME.load(storage, auction_id)?;
+        if env.block.time < start_time {
+            return Err(ContractError::BiddingNotStarted { start_time: start_time.to_string() });
+        }
+
+        let end_time = END_TIME.may_load(storage, auction_id)?;
+        if end_time.is_some_and(|end_time| env.block.time >= end_time) {
+            return Err(ContractError::BiddingClosed {});
+        }
+
+        let owner = OWNER.load(storage, auction_id)?;
+        if *sender == owner {
+            return Err(ContractError::BiddingByOwner {});
+        }
+
+        Ok(())
+    }
+
+    fn ensure_not_paused(storage: &dyn Storage, auction_id: u64) -> Result<(), ContractError> {
+        if PAUSED.load(storage, auction_id)? {
+            return Err(ContractError::Paused {});
+        }
+
+        Ok(())
+    }
+
+    pub fn set_paused(deps: DepsMut, info: MessageInfo, auction_id: u64, paused: bool) -> Result<Response, ContractError> {
+        let owner = OWNER.may_load(deps.storage, auction_id)?;
+        let pauser = PAUSER.may_load(deps.storage, auction_id)?;
+        if owner.as_ref() != Some(&info.sender) && pauser.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {
+                owner: owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            });
+        }
+
+        PAUSED.save(deps.storage, auction_id, &paused)?;
+
+        Ok(Response::new()
+            .add_attribute("action", if paused { "pause" } else { "unpause" })
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn set_pauser(deps: DepsMut, info: MessageInfo, auction_id: u64, pauser: Option<String>) -> Result<Response, ContractError> {
+        let owner = OWNER.may_load(deps.storage, auction_id)?;
+        if owner.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {
+                owner: owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            });
+        }
+
+        let mut resp = Response::new()
+            .add_attribute("action", "set_pauser")
+            .add_attribute("sender", info.sender.as_str());
+
+        match pauser {
+            Some(pauser) => {
+                let pauser = deps.api.addr_validate(&pauser)?;
+                PAUSER.save(deps.storage, auction_id, &pauser)?;
+                resp = resp.add_attribute("pauser", pauser.as_str());
+            }
+            None => PAUSER.remove(deps.storage, auction_id),
+        }
+
+        Ok(resp)
+    }
+
+    /// Bids in a cw20 auction can only be paid through the Receive hook.
+    fn ensure_native(storage: &dyn Storage, auction_id: u64) -> Result<(), ContractError> {
+        if BID_TOKEN.has(storage, auction_id) {
+            return Err(ContractError::InvalidDenomination { denom: BID_ASSET.load(storage, auction_id)?.denom });
+        }
+
+        Ok(())
+    }
+
+    pub fn receive(deps: DepsMut, env: Env, info: MessageInfo, msg: Cw20ReceiveMsg) -> Result<Response, ContractError> {
+        let receive_msg: ReceiveMsg = from_json(&msg.msg)?;
+        let auction_id = match receive_msg {
+            ReceiveMsg::Bid { auction_id } | ReceiveMsg::ProxyBid { auction_id } | ReceiveMsg::Commit { auction_id, .. } | ReceiveMsg::Cancel { auction_id, .. } => auction_id,
+        };
+
+        // Only the token of the auction is accepted, sent by the token contract itself
+        if BID_TOKEN.may_load(deps.storage, auction_id)? != Some(info.sender.clone()) {
+            return Err(ContractError::InvalidDenomination { denom: BID_ASSET.load(deps.storage, auction_id)?.denom });
+        }
+
+        let info = MessageInfo {
+            sender: deps.api.addr_validate(&msg.sender)?,
+            funds: vec![Coin {
+                denom: info.sender.to_string(),
+                amount: msg.amount,
+            }],
+        };
+
+        match receive_msg {
+            ReceiveMsg::Bid { auction_id } => place_bid(deps, env, info, auction_id, false),
+            ReceiveMsg::ProxyBid { auction_id } => place_bid(deps, env, info, auction_id, true),
+            ReceiveMsg::Commit { auction_id, commitment } => place_commit(deps, env, info, auction_id, commitment),
+            ReceiveMsg::Cancel { auction_id, reason } => cancel(deps, env, info, auction_id, reason, true),
+        }
+    }
+
+    pub fn bid(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64) -> Result<Response, ContractError> {
+        ensure_native(deps.storage, auction_id)?;
+
+        place_bid(deps, env, info, auction_id, false)
+    }
+
+    pub fn proxy_bid(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64) -> Result<Response, ContractError> {
+        ensure_native(deps.storage, auction_id)?;
+
+        place_bid(deps, env, info, auction_id, true)
+    }
+
+    /// Raises the highest bid, the whole deposit is bid unless bidding by proxy.
+    fn outbid(storage: &mut dyn Storage, auction_id: u64, bidder: &Addr, deposit: Coin, proxy: bool) -> Result<(), ContractError> {
+        let h_bid = HIGEST_BID.may_load(storage, auction_id)?;
+        let max_bid = PROXY_MAX.may_load(storage, auction_id)?;
+
+        // The highest bidder raising the maximum keeps the current price
+        if proxy && h_bid.as_ref().is_some_and(|hb| hb.address == *bidder) {
+            PROXY_MAX.save(storage, auction_id, &deposit.amount)?;
+            return Ok(());
+        }
+
+        let h_bid_amount = h_bid.as_ref().map(|b| b.bid.amount).unwrap_or(Uint128::new(0));
+        if deposit.amount < h_bid_amount {
+            return Err(ContractError::BidToLow { higest_bid: h_bid_amount.to_string() });
+        }
+
+        let min_bid = super::min_next_bid(storage, auction_id)?;
+        if deposit.amount < min_bid {
+            return Err(ContractError::BidIncrementToLow { min_bid: min_bid.to_string() });
+        }
+
+        if let Some(mut hb) = h_bid {
+            if hb.address != *bidder {
+                match max_bid {
+                    // The proxy bids just above the challenger, on a tie the earlier bid wins
+                    Some(max_bid) if deposit.amount <= max_bid => {
+                        let price = super::next_bid(storage, auction_id, deposit.amount)?.min(max_bid);
+
+                        RUNNER_UP.save(storage, auction_id, &Bid {
+                            address: bidder.clone(),
+                            bid: deposit,
+                        })?;
+
+                        hb.bid.amount = price;
+                        HIGEST_BID.save(storage, auction_id, &hb)?;
+
+                        return Ok(());
+                    }
+                    // The outbid highest bidder becomes the runner-up, a proxy at its maximum
+                    Some(max_bid) => {
+                        hb.bid.amount = max_bid;
+                        RUNNER_UP.save(storage, auction_id, &hb)?;
+                    }
+                    None => RUNNER_UP.save(storage, auction_id, &hb)?,
+                }
+            }
+        }
+
+        let mut bid = deposit.clone();
+        if proxy {
+            // A proxy only bids what it takes to lead, starting at the reserve price unless it is hidden
+            let reserve = match HIDDEN_RESERVE.load(storage, auction_id)? {
+                true => Uint128::new(0),
+                false => BID_ASSET.load(storage, auction_id)?.amount,
+            };
+            let price = match RUNNER_UP.may_load(storage, auction_id)? {
+                Some(runner_up) => super::next_bid(storage, auction_id, runner_up.bid.amount)?,
+                None => Uint128::new(1),
+            };
+            bid.amount = price.max(reserve).min(deposit.amount);
+
+            PROXY_MAX.save(storage, auction_id, &deposit.amount)?;
+        } else {
+            PROXY_MAX.remove(storage, auction_id);
+        }
+
+        HIGEST_BID.save(storage, auction_id, &Bid {
+            address: bidder.clone(),
+            bid,
+        })?;
+
+        Ok(())
+    }
+
+    fn place_bid(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64, proxy: bool) -> Result<Response, ContractError> {
+        let mut resp = Response::new();
+
+        ensure_not_paused(deps.storage, auction_id)?;
+
+        match MODE.load(deps.storage, auction_id)? {
+            AuctionMode::Open {} => {}
+            AuctionMode::Dutch { .. } if !proxy => return dutch_bid(deps, env, info, auction_id),
+            AuctionMode::Dutch { .. } => return Err(ContractError::InvalidMode {}),
+            AuctionMode::Sealed { .. } => return Err(ContractError::InvalidMode {}),
+        }
+
+        ensure_bidding(deps.storage, auction_id, &env, &info.sender)?;
+
+        let end_time = END_TIME.may_load(deps.storage, auction_id)?;
+        let owner = OWNER.load(deps.storage, auction_id)?;
+
+        let asset = BID_ASSET.load(deps.storage, auction_id)?;
+        
+        match info.funds.iter().find(|c| c.denom == asset.denom) {
+            Some(funds) => {
+                // A bid reaching the buy-now price pays exactly that price, anything above it is refunded
+                let mut funds = funds.clone();
+                let mut refund = Uint128::new(0);
+                if let Some(buy_now) = BUY_NOW.may_load(deps.storage, auction_id)? {
+                    let placed = BIDS.may_load(deps.storage, (auction_id, info.sender.clone()))?.map_or(Uint128::new(0), |deposit| deposit.gross.amount);
+                    let cap = buy_now.saturating_sub(placed);
+                    if funds.amount > cap {
+                        refund = funds.amount - cap;
+                        funds.amount = cap;
+                    }
+                }
+
+                let leader = super::commission_holder(deps.storage, auction_id)?;
+                let (deposit, com_amount) = add_deposit(deps.storage, &env.block, auction_id, &info.sender, &funds)?;
+
+                outbid(deps.storage, auction_id, &info.sender, deposit.gross, proxy)?;
+                let owed = hold_commission(deps.storage, auction_id, leader, &info.sender, com_amount)?;
+
+                // Extend the end time when the highest bid is raised within the soft close window
+                if let (Some(end_time), Some(soft_close)) = (end_time, SOFT_CLOSE.may_load(deps.storage, auction_id)?) {
+                    if env.block.time.plus_seconds(soft_close.window) >= end_time {
+                        let end_time = end_time.plus_seconds(soft_close.extension);
+                        END_TIME.save(deps.storage, auction_id, &end_time)?;
+                        EXTENSIONS.update(deps.storage, auction_id, |extensions| -> StdResult<_> { Ok(extensions.unwrap_or_default() + 1) })?;
+
+                        resp = resp.add_attribute("end_time", end_time.to_string());
+                    }
+                }
+
+                if !refund.is_zero() {
+                    let transfer = super::send_asset(deps.storage, auction_id, info.sender.to_string(), Coin {
+                        denom: funds.denom.clone(),
+                        amount: refund,
+                    })?;
+
+                    resp = resp.add_message(transfer);
+                }
+
+                let fees = charge_commission(deps.storage, auction_id, Coin {
+                    denom: funds.denom.clone(),
+                    amount: owed,
+                })?;
+                resp = resp.add_messages(fees);
+
+                resp = resp
+                .add_attribute("action", "bid")
+                .add_attribute("sender", info.sender.as_str())
+                .add_attribute("commission", com_amount.to_string());
+
+                // Reaching the buy-now price closes the auction right away
+                if let Some(buy_now) = BUY_NOW.may_load(deps.storage, auction_id)? {
+                    if HIGEST_BID.load(deps.storage, auction_id)?.bid.amount >= buy_now {
+                        resp = settle(deps.storage, &env.block, auction_id, resp, &owner)?
+                        .add_attribute("buy_now", buy_now.to_string())
+                        .add_attribute("bidding", "closed");
+                    }
+                }
+
+                Ok(resp)
+            },
+            None => Err(ContractError::InvalidDenomination {denom: asset.denom}),
+        }
+    }
+
+    /// The first bid paying the current price wins and closes the auction.
+    fn dutch_bid(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64) -> Result<Response, ContractError> {
+        let mut resp = Response::new();
+
+        ensure_bidding(deps.storage, auction_id, &env, &info.sender)?;
+
+        let owner = OWNER.load(deps.storage, auction_id)?;
+        let asset = BID_ASSET.load(deps.storage, auction_id)?;
+
+        let funds = match info.funds.iter().find(|c| c.denom == asset.denom) {
+            Some(funds) => funds,
+            None => return Err(ContractError::InvalidDenomination { denom: asset.denom }),
+        };
+
+        let price = super::dutch_price(deps.storage, auction_id, env.block.time)?.unwrap_or_default();
+        if funds.amount < price {
+            return Err(ContractError::BidBelowPrice { price: price.to_string() });
+        }
+
+        let (deposit, com_amount) = add_deposit(deps.storage, &env.block, auction_id, &info.sender, &Coin {
+            denom: funds.denom.clone(),
+            amount: price,
+        })?;
+
+        HIGEST_BID.save(deps.storage, auction_id, &Bid {
+            address: info.sender.clone(),
+            bid: deposit.gross,
+        })?;
+
+        // Refund anything paid above the current price
+        let refund = funds.amount - price;
+        if !refund.is_zero() {
+            let transfer = super::send_asset(deps.storage, auction_id, info.sender.to_string(), Coin {
+                denom: funds.denom.clone(),
+                amount: refund,
+            })?;
+
+            resp = resp.add_message(transfer);
+        }
+
+        let fees = charge_commission(deps.storage, auction_id, Coin {
+            denom: funds.denom.clone(),
+            amount: com_amount,
+        })?;
+        resp = resp.add_messages(fees);
+
+        resp = resp
+        .add_attribute("action", "bid")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("commission", com_amount.to_string())
+        .add_attribute("price", price.to_string());
+
+        let resp = settle(deps.storage, &env.block, auction_id, resp, &owner)?
+        .add_attribute("bidding", "closed");
+
+        Ok(resp)
+    }
+
+    pub fn close(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64) -> Result<Response, ContractError> {
+        let is_open = IS_OPEN.load(deps.storage, auction_id)?;
+        if !is_open {
+            return Err(ContractError::BiddingClosed {});
+        }
+
+        ensure_not_paused(deps.storage, auction_id)?;
+
+        let owner = OWNER.load(deps.storage, auction_id)?;
+        let mut resp = Response::new();
+
+        // Anyone can close the auction once the end time has passed
+        let closing_time = super::closing_time(deps.storage, auction_id)?;
+        let has_ended = closing_time.is_some_and(|closing_time| env.block.time >= closing_time);
+
+        // Sealed bids can not be cut short, not even by the owner
+        if !has_ended && matches!(MODE.load(deps.storage, auction_id)?, AuctionMode::Sealed { .. }) {
+            return Err(ContractError::BiddingOpen {});
+        }
+
+        if info.sender != owner && !has_ended {
+            return Err(ContractError::Unauthorized {
+                owner: owner.to_string(),
+            });
+        }
+
+        resp = settle(deps.storage, &env.block, auction_id, resp, &owner)?;
+
+        resp = resp
+        .add_attribute("action", "close")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("bidding", "closed");
+
+        Ok(resp)
+    }
+
+    /// Ends the auction without a winner, every bid and the NFT are returned.
+    /// Refunding commission already paid out requires the owner to send it along.
+    pub fn cancel(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64, reason: Option<String>, refund_commission: bool) -> Result<Response, ContractError> {
+        let owner = OWNER.may_load(deps.storage, auction_id)?;
+        if owner.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {
+                owner: owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            });
+        }
+
+        // An ended auction only waits to be closed, its result can not be backed out of
+        let has_ended = super::closing_time(deps.storage, auction_id)?.is_some_and(|closing_time| env.block.time >= closing_time);
+        if !IS_OPEN.load(deps.storage, auction_id)? || has_ended {
+            return Err(ContractError::BiddingClosed {});
+        }
+
+        let mut resp = Response::new();
+        let denom = BID_ASSET.load(deps.storage, auction_id)?.denom;
+        let paid = FEES
+            .prefix(auction_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, fee)| fee))
+            .sum::<StdResult<Uint128>>()?;
+
+        let required = match refund_commission {
+            true => paid,
+            false => Uint128::new(0),
+        };
+        let sent = info.funds.iter().find(|coin| coin.denom == denom).map_or(Uint128::new(0), |coin| coin.amount);
+        if sent != required || info.funds.iter().any(|coin| coin.denom != denom) {
+            return Err(ContractError::CommissionRefund { amount: required.to_string() });
+        }
+        ESCROWED.update(deps.storage, auction_id, |escrowed| -> StdResult<_> { Ok(escrowed.unwrap_or_default() + sent) })?;
+
+        // Escrowed commission was never paid out and goes back with the bids
+        let commission = match refund_commission {
+            true => Uint128::new(0),
+            false => paid,
+        };
+
+        // Commission held for the leader goes back with its bid
+        if let Some(leader) = super::commission_holder(deps.storage, auction_id)? {
+            let held = HELD_COMMISSION.may_load(deps.storage, auction_id)?.unwrap_or_default();
+            HELD_COMMISSION.remove(deps.storage, auction_id);
+
+            let mut deposit = BIDS.load(deps.storage, (auction_id, leader.clone()))?;
+            deposit.commission -= held;
+            deposit.net += held;
+            BIDS.save(deps.storage, (auction_id, leader), &deposit)?;
+        }
+
+        IS_OPEN.save(deps.storage, auction_id, &false)?;
+        SOLD.save(deps.storage, auction_id, &false)?;
+        CANCELLATION.save(deps.storage, auction_id, &Cancellation {
+            reason: reason.clone(),
+            refund_commission,
+        })?;
+        SETTLEMENT.save(deps.storage, auction_id, &Settlement {
+            winner: None,
+            clearing_price: Uint128::new(0),
+            commission,
+            proceeds: Uint128::new(0),
+            height: env.block.height,
+            time: env.block.time,
+            outcome: Outcome::Cancelled {},
+        })?;
+
+        if let Some(nft) = NFT.may_load(deps.storage, auction_id)? {
+            resp = resp.add_submessage(transfer_nft(nft, &info.sender)?);
+        }
+
+        if let Some(reason) = reason {
+            resp = resp.add_attribute("reason", reason);
+        }
+
+        Ok(resp
+            .add_submessages(super::update_registry(deps.storage, auction_id)?)
+            .add_attribute("action", "cancel")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("bidding", "closed"))
+    }
+
+    /// Closes the auction and pays the highest bid to the owner if the reserve price is met.
+    fn settle(storage: &mut dyn Storage, block: &BlockInfo, auction_id: u64, mut resp: Response, owner: &Addr) -> Result<Response, ContractError> {
+        IS_OPEN.save(storage, auction_id, &false)?;
+
+        let winner = HIGEST_BID.may_load(storage, auction_id)?;
+        let reserve = BID_ASSET.load(storage, auction_id)?.amount;
+        let mut sold = false;
+        let mut recipient = owner.clone();
+        let mut price = Uint128::new(0);
+        let mut proceeds = Uint128::new(0);
+
+        match winner {
+            Some(mut winner) => {
+                let mut deposit = BIDS.load(storage, (auction_id, winner.address.clone()))?;
+
+                // A proxy below a hidden reserve price bids up to it on close
+                if PROXY_MAX.may_load(storage, auction_id)?.is_some_and(|max_bid| winner.bid.amount < reserve && max_bid >= reserve) {
+                    winner.bid.amount = reserve;
+                    HIGEST_BID.save(storage, auction_id, &winner)?;
+                }
+
+                // Below the reserve price the item is unsold and all bidders can retract
+                if winner.bid.amount >= reserve {
+                    sold = true;
+                    recipient = winner.address.clone();
+                    price = super::clearing_price(storage, auction_id, &winner, reserve)?;
+
+                    let fees = settle_commission(storage, auction_id, &mut deposit, price, true)?;
+                    resp = resp.add_messages(fees);
+
+                    // The commission is paid, the surplus over the clearing price stays retractable
+                    let surplus = (deposit.gross.amount - price).min(deposit.net);
+                    let payout = deposit.net - surplus;
+                    proceeds += payout;
+                    ESCROWED.update(storage, auction_id, |escrowed| -> StdResult<_> { Ok(escrowed.unwrap_or_default() - payout) })?;
+
+                    deposit.net = surplus;
+                    BIDS.save(storage, (auction_id, winner.address.clone()), &deposit)?;
+
+                    CLEARING_PRICE.save(storage, auction_id, &price)?;
+
+                    if !payout.is_zero() {
+                        let transfer = super::send_asset(storage, auction_id, owner.to_string(), Coin {
+                            denom: deposit.gross.denom,
+                            amount: payout,
+                        })?;
+
+                        resp = resp.add_message(transfer);
+                    }
+
+                    resp = resp
+                    .add_attribute("winner", winner.address.as_str())
+                    .add_attribute("clearing_price", price.to_string());
+                } else {
+                    let fees = settle_commission(storage, auction_id, &mut deposit, winner.bid.amount, false)?;
+                    BIDS.save(storage, (auction_id, winner.address.clone()), &deposit)?;
+
+                    resp = resp
+                    .add_messages(fees)
+                    .add_attribute("winner", "None")
+                    .add_attribute("reserve", "not_met");
+                }
+            }
+            None => {
+                resp = resp.add_attribute("winner", "None");
+            }
+        }
+
+        SOLD.save(storage, auction_id, &sold)?;
+
+        // Escrowed NFT goes to the winner, or back to the owner without a sale
+        if let Some(nft) = NFT.may_load(storage, auction_id)? {
+            resp = resp.add_submessage(transfer_nft(nft, &recipient)?);
+        }
+
+        // Unrevealed deposits are forfeited to the owner
+        if let AuctionMode::Sealed { forfeit_unrevealed: true, .. } = MODE.load(storage, auction_id)? {
+            let unrevealed = UNREVEALED.load(storage, auction_id)?;
+
+            if !unrevealed.is_zero() {
+                let transfer = super::send_asset(storage, auction_id, owner.to_string(), Coin {
+                    denom: BID_ASSET.load(storage, auction_id)?.denom,
+                    amount: unrevealed,
+                })?;
+
+                UNREVEALED.save(storage, auction_id, &Uint128::new(0))?;
+                proceeds += unrevealed;
+
+                resp = resp
+                .add_message(transfer)
+                .add_attribute("forfeited", unrevealed.to_string());
+            }
+        }
+
+        let commission = FEES
+            .prefix(auction_id)
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, fee)| fee))
+            .sum::<StdResult<Uint128>>()?;
+
+        SETTLEMENT.save(storage, auction_id, &Settlement {
+            winner: sold.then_some(recipient),
+            clearing_price: price,
+            commission,
+            proceeds,
+            height: block.height,
+            time: block.time,
+            outcome: match sold {
+                true => Outcome::Sold {},
+                false => Outcome::Unsold {},
+            },
+        })?;
+
+        Ok(resp.add_submessages(super::update_registry(storage, auction_id)?))
+    }
+
+    pub fn retract(deps: DepsMut, info: MessageInfo, auction_id: u64, receiver: Option<String>) -> Result<Response, ContractError> {
+        ensure_not_paused(deps.storage, auction_id)?;
+
+        let claim = super::claimable(deps.storage, auction_id, &info.sender)?;
+        let addr = receiver.unwrap_or(info.sender.to_string());
+
+        // Claims are recorded so the same funds can not be retracted twice
+        if COMMITMENTS.has(deps.storage, (auction_id, info.sender.clone())) {
+            COMMITMENTS.remove(deps.storage, (auction_id, info.sender));
+            UNREVEALED.update(deps.storage, auction_id, |unrevealed| -> StdResult<_> { Ok(unrevealed.unwrap_or_default() - claim.amount) })?;
+        } else {
+            CLAIMED.update(deps.storage, (auction_id, info.sender), |claimed| -> StdResult<_> { Ok(claimed.unwrap_or_default() + claim.amount) })?;
+            ESCROWED.update(deps.storage, auction_id, |escrowed| -> StdResult<_> { Ok(escrowed.unwrap_or_default() - claim.amount) })?;
+        }
+
+        let transfer = super::send_asset(deps.storage, auction_id, addr.clone(), claim.clone())?;
+
+        Ok(Response::new()
+            .add_message(transfer)
+            .add_attribute("action", "retract")
+            .add_attribute("sender", addr)
+            .add_attribute("amount", claim.amount.to_string()))
+    }
+
+    pub fn propose_owner(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64, new_owner: String, expiry: Option<Expiration>) -> Result<Response, ContractError> {
+        let owner = OWNER.may_load(deps.storage, auction_id)?;
+        if owner.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {
+                owner: owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            });
+        }
+
+        let expiry = expiry.unwrap_or_default();
+        if expiry.is_expired(&env.block) {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+
+        let new_owner = deps.api.addr_validate(&new_owner)?;
+        PENDING_OWNER.save(deps.storage, auction_id, &PendingOwner {
+            address: new_owner.clone(),
+            expiry,
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "propose_owner")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("pending_owner", new_owner.as_str())
+            .add_attribute("expiry", expiry.to_string()))
+    }
+
+    pub fn accept_ownership(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64) -> Result<Response, ContractError> {
+        let pending = PENDING_OWNER.may_load(deps.storage, auction_id)?.ok_or(ContractError::NoPendingOwner {})?;
+        if info.sender != pending.address {
+            return Err(ContractError::Unauthorized {
+                owner: pending.address.to_string(),
+            });
+        }
+
+        if pending.expiry.is_expired(&env.block) {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+
+        // The owner can not bid, bidders can not take over
+        if BIDS.has(deps.storage, (auction_id, info.sender.clone())) || COMMITMENTS.has(deps.storage, (auction_id, info.sender.clone())) {
+            return Err(ContractError::BiddingByOwner {});
+        }
+
+        PENDING_OWNER.remove(deps.storage, auction_id);
+        OWNER.save(deps.storage, auction_id, &info.sender)?;
+
+        Ok(Response::new()
+            .add_submessages(super::update_registry(deps.storage, auction_id)?)
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("owner", info.sender.as_str()))
+    }
+
+    /// Only closed auctions can be left without an owner, open ones still pay the owner.
+    pub fn renounce_ownership(deps: DepsMut, info: MessageInfo, auction_id: u64) -> Result<Response, ContractError> {
+        let owner = OWNER.may_load(deps.storage, auction_id)?;
+        if owner.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {
+                owner: owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            });
+        }
+
+        if IS_OPEN.load(deps.storage, auction_id)? {
+            return Err(ContractError::BiddingOpen {});
+        }
+
+        OWNER.remove(deps.storage, auction_id);
+        PENDING_OWNER.remove(deps.storage, auction_id);
+
+        Ok(Response::new()
+            .add_submessages(super::update_registry(deps.storage, auction_id)?)
+            .add_attribute("action", "renounce_ownership")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn commit(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64, commitment: Binary) -> Result<Response, ContractError> {
+        ensure_native(deps.storage, auction_id)?;
+
+        place_commit(deps, env, info, auction_id, commitment)
+    }
+
+    fn place_commit(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64, commitment: Binary) -> Result<Response, ContractError> {
+        ensure_not_paused(deps.storage, auction_id)?;
+
+        if !matches!(MODE.load(deps.storage, auction_id)?, AuctionMode::Sealed { .. }) {
+            return Err(ContractError::InvalidMode {});
+        }
+
+        ensure_bidding(deps.storage, auction_id, &env, &info.sender)?;
+
+        if COMMITMENTS.has(deps.storage, (auction_id, info.sender.clone())) {
+            return Err(ContractError::AlreadyCommitted {});
+        }
+
+        let asset = BID_ASSET.load(deps.storage, auction_id)?;
+        let deposit = match info.funds.iter().find(|c| c.denom == asset.denom) {
+            Some(funds) => funds.clone(),
+            None => return Err(ContractError::InvalidDenomination { denom: asset.denom }),
+        };
+
+        UNREVEALED.update(deps.storage, auction_id, |unrevealed| -> StdResult<_> { Ok(unrevealed.unwrap_or_default() + deposit.amount) })?;
+        COMMITMENTS.save(deps.storage, (auction_id, info.sender.clone()), &Commitment {
+            commitment,
+            deposit: deposit.clone(),
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "commit")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("deposit", deposit.amount.to_string()))
+    }
+
+    pub fn reveal(deps: DepsMut, env: Env, info: MessageInfo, auction_id: u64, amount: Uint128, salt: String) -> Result<Response, ContractError> {
+        let mut resp = Response::new();
+
+        ensure_not_paused(deps.storage, auction_id)?;
+
+        let reveal_period = match MODE.load(deps.storage, auction_id)? {
+            AuctionMode::Sealed { reveal_period, .. } => reveal_period,
+            _ => return Err(ContractError::InvalidMode {}),
+        };
+
+        if !IS_OPEN.load(deps.storage, auction_id)? {
+            return Err(ContractError::BiddingClosed {});
+        }
+
+        let end_time = END_TIME.load(deps.storage, auction_id)?;
+        if env.block.time < end_time || env.block.time >= end_time.plus_seconds(reveal_period) {
+            return Err(ContractError::RevealNotOpen {});
+        }
+
+        let commitment = match COMMITMENTS.may_load(deps.storage, (auction_id, info.sender.clone()))? {
+            Some(commitment) => commitment,
+            None => return Err(ContractError::NoCommitment {}),
+        };
+
+        if amount.is_zero()
+            || amount > commitment.deposit.amount
+            || sealed_bid_commitment(info.sender.as_str(), amount, &salt) != commitment.commitment
+        {
+            return Err(ContractError::InvalidReveal {});
+        }
+
+        COMMITMENTS.remove(deps.storage, (auction_id, info.sender.clone()));
+        UNREVEALED.update(deps.storage, auction_id, |unrevealed| -> StdResult<_> { Ok(unrevealed.unwrap_or_default() - commitment.deposit.amount) })?;
+
+        let denom = commitment.deposit.denom;
+        let (deposit, com_amount) = add_deposit(deps.storage, &env.block, auction_id, &info.sender, &Coin {
+            denom: denom.clone(),
+            amount,
+        })?;
+
+        let bid = Bid {
+            address: info.sender.clone(),
+            bid: deposit.gross,
+        };
+
+        let leader = super::commission_holder(deps.storage, auction_id)?;
+
+        // Ties are won by the bid revealed first
+        match HIGEST_BID.may_load(deps.storage, auction_id)? {
+            Some(hb) if amount <= hb.bid.amount => {
+                if RUNNER_UP.may_load(deps.storage, auction_id)?.is_none_or(|ru| amount > ru.bid.amount) {
+                    RUNNER_UP.save(deps.storage, auction_id, &bid)?;
+                }
+            }
+            h_bid => {
+                if let Some(hb) = h_bid {
+                    RUNNER_UP.save(deps.storage, auction_id, &hb)?;
+                }
+                HIGEST_BID.save(deps.storage, auction_id, &bid)?;
+            }
+        }
+
+        // Refund the deposit above the revealed bid
+        let refund = commitment.deposit.amount - amount;
+        if !refund.is_zero() {
+            let transfer = super::send_asset(deps.storage, auction_id, info.sender.to_string(), Coin {
+                denom: denom.clone(),
+                amount: refund,
+            })?;
+
+            resp = resp.add_message(transfer);
+        }
+
+        let owed = hold_commission(deps.storage, auction_id, leader, &info.sender, com_amount)?;
+        let fees = charge_commission(deps.storage, auction_id, Coin {
+            denom,
+            amount: owed,
+        })?;
+        resp = resp.add_messages(fees);
+
+        resp = resp
+        .add_attribute("action", "reveal")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("commission", com_amount.to_string());
+
+        Ok(resp)
+    }
+}
+
+mod migrate {
+    use cosmwasm_std::{Coin, Decimal, DepsMut, Env, MessageInfo, Order, StdResult, Uint128};
+
+    use crate::error::ContractError;
+    use crate::msg::{Commission, InstantiateMsg, Outcome};
+    use crate::state::{legacy, Bid, Deposit, Settlement, AUCTION_COUNT, BID_COUNT, BIDS, CLAIMED, CLEARING_PRICE, ESCROWED, HIGEST_BID, IS_OPEN, SETTLEMENT, SOLD};
+
+    /// The single auction of 0.1.0 becomes auction 1.
+    pub fn v0_2_0(mut deps: DepsMut, env: &Env) -> Result<(), ContractError> {
+        let owner = legacy::OWNER.load(deps.storage)?;
+        let bid_asset = legacy::BID_ASSET.load(deps.storage)?;
+        let commission = legacy::COMMISSION.load(deps.storage)?;
+        let is_open = legacy::IS_OPEN.load(deps.storage)?;
+        let commodity = legacy::COMMODITY.load(deps.storage)?;
+        let balance = deps.querier.query_balance(&env.contract.address, &bid_asset.denom)?;
+
+        AUCTION_COUNT.save(deps.storage, &0)?;
+        super::exec::create_auction(deps.branch(), env.clone(), MessageInfo {
+            sender: owner.clone(),
+            funds: vec![],
+        }, InstantiateMsg {
+            commodity,
+            // The amount was never a reserve price
+            bid_asset: Coin {
+                denom: bid_asset.denom,
+                amount: Uint128::new(0),
+            },
+            commission: Commission::Rate { rate: Decimal::from_ratio(commission, 100u128) },
+            commission_policy: None,
+            fee_recipients: None,
+            owner: Some(owner.to_string()),
+            start_time: None,
+            end_time: None,
+            soft_close: None,
+            hidden_reserve: false,
+            min_increment: None,
+            buy_now: None,
+            mode: None,
+            second_price: false,
+            cw20: false,
+            pauser: None,
+            registry: None,
+        })?;
+        let auction_id = AUCTION_COUNT.load(deps.storage)?;
+
+        // Legacy bids share the namespace of the new ones, all are read before saving any
+        let bids = legacy::BIDS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        BID_COUNT.save(deps.storage, auction_id, &(bids.len() as u64))?;
+        ESCROWED.save(deps.storage, auction_id, &bids.iter().map(|(_, bid)| bid.amount).sum())?;
+        for (address, bid) in bids {
+            legacy::BIDS.remove(deps.storage, address.clone());
+
+            // The commission was paid on every bid
+            BIDS.save(deps.storage, (auction_id, address), &Deposit {
+                gross: bid.clone(),
+                commission: Uint128::new(0),
+                net: bid.amount,
+            })?;
+        }
+
+        // The highest bidder paid the whole deposit on close
+        let winner = legacy::HIGEST_BID.may_load(deps.storage)?;
+        let mut price = Uint128::new(0);
+        if let Some(winner) = &winner {
+            let mut deposit = BIDS.load(deps.storage, (auction_id, winner.address.clone()))?;
+
+            HIGEST_BID.save(deps.storage, auction_id, &Bid {
+                address: winner.address.clone(),
+                bid: deposit.gross.clone(),
+            })?;
+
+            if !is_open {
+                ESCROWED.update(deps.storage, auction_id, |escrowed| -> StdResult<_> { Ok(escrowed.unwrap_or_default() - deposit.net) })?;
+                CLEARING_PRICE.save(deps.storage, auction_id, &deposit.net)?;
+                price = deposit.net;
+                deposit.net = Uint128::new(0);
+                BIDS.save(deps.storage, (auction_id, winner.address.clone()), &deposit)?;
+            }
+        }
+
+        if !is_open {
+            IS_OPEN.save(deps.storage, auction_id, &false)?;
+            SOLD.save(deps.storage, auction_id, &winner.is_some())?;
+
+            // 0.1.0 kept no record of the fees, the commission was paid out on every bid
+            SETTLEMENT.save(deps.storage, auction_id, &Settlement {
+                winner: winner.as_ref().map(|winner| winner.address.clone()),
+                clearing_price: price,
+                commission: Uint128::new(0),
+                proceeds: price,
+                height: env.block.height,
+                time: env.block.time,
+                outcome: match winner {
+                    Some(_) => Outcome::Sold {},
+                    None => Outcome::Unsold {},
+                },
+            })?;
+
+            // 0.1.0 never recorded retracted bids, only the balance tells whether the losing bids are still held
+            let escrowed = ESCROWED.load(deps.storage, auction_id)?;
+            if balance.amount.is_zero() {
+                let losers = BIDS
+                    .prefix(auction_id)
+                    .range(deps.storage, None, None, Order::Ascending)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for (address, deposit) in losers {
+                    CLAIMED.save(deps.storage, (auction_id, address), &deposit.net)?;
+                }
+                ESCROWED.save(deps.storage, auction_id, &Uint128::new(0))?;
+            } else if balance.amount < escrowed {
+                return Err(ContractError::PartialRetraction {});
+            }
+        }
+
+        legacy::OWNER.remove(deps.storage);
+        legacy::COMMODITY.remove(deps.storage);
+        legacy::BID_ASSET.remove(deps.storage);
+        legacy::COMMISSION.remove(deps.storage);
+        legacy::IS_OPEN.remove(deps.storage);
+        legacy::HIGEST_BID.remove(deps.storage);
+
+        Ok(())
     }
 }
\ No newline at end of file
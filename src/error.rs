@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw_utils::ParseReplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,15 +7,51 @@ pub enum ContractError {
     #[error("{0}.")]
     Std(#[from] StdError),
 
+    #[error("{0}.")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}.")]
+    SemVer(String),
+
     #[error("Unauthorized - only {owner} can call it.")]
     Unauthorized { owner: String },
 
+    #[error("Auction is paused.")]
+    Paused {},
+
     #[error("Bidding is closed.")]
     BiddingClosed {},
 
     #[error("Bidding is still open.")]
     BiddingOpen {},
 
+    #[error("Bidding opens at {start_time}.")]
+    BiddingNotStarted { start_time: String },
+
+    #[error("End time must be after start time and in the future.")]
+    InvalidTimeWindow {},
+
+    #[error("Soft close requires an end time and a non-zero window and extension.")]
+    InvalidSoftClose {},
+
+    #[error("Commission rates must be at most 100% and tiers in ascending order.")]
+    InvalidCommission {},
+
+    #[error("Fee recipient shares must add up to 100%.")]
+    InvalidFeeRecipients {},
+
+    #[error("Bid does not cover the commission of {commission}.")]
+    BidBelowCommission { commission: String },
+
+    #[error("Percentage bid increment must be at most 100%.")]
+    InvalidIncrement {},
+
+    #[error("Buy-now price must be above zero and not below the reserve price, without second-price settlement.")]
+    InvalidBuyNow {},
+
+    #[error("NFT is not held by the contract.")]
+    NftNotHeld {},
+
     #[error("Owner can not bid.")]
     BiddingByOwner { },
 
@@ -24,9 +61,69 @@ pub enum ContractError {
     #[error("Bid is to low, current highest bid is {higest_bid}.")]
     BidToLow { higest_bid: String },
 
+    #[error("Bid increment is to low, minimum next bid is {min_bid}.")]
+    BidIncrementToLow { min_bid: String },
+
+    #[error("Not supported in this auction mode.")]
+    InvalidMode {},
+
+    #[error("Sealed auction requires an end time and a reveal period, without soft close or buy-now price.")]
+    InvalidSealedAuction {},
+
+    #[error("Dutch auction requires a start price above the floor price and a decline period, without reserve price or other pricing options.")]
+    InvalidDutchAuction {},
+
+    #[error("Bid is to low, current price is {price}.")]
+    BidBelowPrice { price: String },
+
+    #[error("Bid is already committed.")]
+    AlreadyCommitted {},
+
+    #[error("Reveal phase is not open.")]
+    RevealNotOpen {},
+
+    #[error("No committed bid.")]
+    NoCommitment {},
+
+    #[error("Revealed bid does not match the commitment or exceeds the deposit.")]
+    InvalidReveal {},
+
+    #[error("Unrevealed deposit is forfeited.")]
+    DepositForfeited {},
+
     #[error("Winner can not retract funds.")]
     RetractByWinner {},
 
     #[error("No placed bids.")]
     NoBid {},
+
+    #[error("Funds are already retracted.")]
+    AlreadyClaimed {},
+
+    #[error("Cancelling requires sending the commission refund of {amount}.")]
+    CommissionRefund { amount: String },
+
+    #[error("No ownership transfer is proposed.")]
+    NoPendingOwner {},
+
+    #[error("Ownership transfer proposal has expired.")]
+    OwnershipProposalExpired {},
+
+    #[error("Contract is not registered by the factory.")]
+    UnregisteredContract {},
+
+    #[error("Can not migrate from contract {contract}.")]
+    InvalidContract { contract: String },
+
+    #[error("Can not migrate from version {version} to an older version.")]
+    Downgrade { version: String },
+
+    #[error("Some losing bids of the closed auction are retracted, retract the rest before migrating.")]
+    PartialRetraction {},
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        ContractError::SemVer(err.to_string())
+    }
 }
\ No newline at end of file
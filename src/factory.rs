@@ -0,0 +1,51 @@
+//! Factory that instantiates bidding contracts and keeps a registry of them.
+//! Build it with the `factory` feature to export these entry points instead of the bidding contract's.
+#[cfg(feature = "factory")]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, to_json_binary};
+
+use crate::error::ContractError;
+
+mod contract;
+pub mod msg;
+mod state;
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn instantiate(deps: DepsMut, env: Env, info: MessageInfo, msg: msg::InstantiateMsg) -> StdResult<Response> {
+    contract::instantiate(deps, env, info, msg)
+}
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+    use msg::QueryMsg::*;
+    use contract::query;
+
+    match msg {
+        Config {} => to_json_binary(&query::config(deps)?),
+        Auctions { start_after, limit } => to_json_binary(&query::auctions(deps, start_after, limit)?),
+        AuctionsByOwner { owner, start_after, limit } => to_json_binary(&query::auctions_by_owner(deps, owner, start_after, limit)?),
+        AuctionsByStatus { is_open, start_after, limit } => to_json_binary(&query::auctions_by_status(deps, is_open, start_after, limit)?),
+    }
+}
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: msg::ExecMsg) -> Result<Response, ContractError> {
+    use contract::exec;
+    use msg::ExecMsg::*;
+
+    match msg {
+        CreateAuction(msg) => exec::create_auction(deps, env, info, *msg),
+        UpdateAuction { auction_id, owner, is_open } => exec::update_auction(deps, info, auction_id, owner, is_open),
+        MigrateAuction { contract, code_id } => exec::migrate_auction(deps, env, info, contract, code_id),
+    }
+}
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    use contract::{exec, INSTANTIATE_REPLY_ID};
+
+    match msg.id {
+        INSTANTIATE_REPLY_ID => exec::register_auction(deps, msg),
+        id => Err(StdError::generic_err(format!("Unknown reply id {id}")).into()),
+    }
+}
@@ -0,0 +1,209 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult};
+
+use super::msg::InstantiateMsg;
+use super::state::BIDDING_CODE_ID;
+
+const CONTRACT_NAME: &str = concat!(env!("CARGO_PKG_NAME"), "_factory");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub const INSTANTIATE_REPLY_ID: u64 = 1;
+
+pub fn instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    BIDDING_CODE_ID.save(deps.storage, &msg.bidding_code_id)?;
+
+    Ok(Response::new())
+}
+
+pub mod query {
+    use cosmwasm_std::{Addr, Deps, Order, StdResult};
+    use cw_storage_plus::Bound;
+
+    use crate::factory::msg::{AuctionInfo, AuctionsResp, ConfigResp};
+    use crate::factory::state::{AuctionRecord, AUCTIONS, BIDDING_CODE_ID};
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    pub fn config(deps: Deps) -> StdResult<ConfigResp> {
+        let bidding_code_id = BIDDING_CODE_ID.load(deps.storage)?;
+
+        Ok(ConfigResp { bidding_code_id })
+    }
+
+    fn auction_info(((address, auction_id), auction): ((Addr, u64), AuctionRecord)) -> AuctionInfo {
+        AuctionInfo {
+            address: address.to_string(),
+            auction_id,
+            owner: auction.owner.map(|owner| owner.to_string()),
+            is_open: auction.is_open,
+        }
+    }
+
+    fn start_bound(deps: Deps, start_after: Option<(String, u64)>) -> StdResult<Option<Bound<'static, (Addr, u64)>>> {
+        start_after
+            .map(|(addr, auction_id)| deps.api.addr_validate(&addr).map(|addr| Bound::exclusive((addr, auction_id))))
+            .transpose()
+    }
+
+    pub fn auctions(deps: Deps, start_after: Option<(String, u64)>, limit: Option<u32>) -> StdResult<AuctionsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_bound(deps, start_after)?;
+
+        let auctions = AUCTIONS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(auction_info))
+            .collect::<StdResult<_>>()?;
+
+        Ok(AuctionsResp { auctions })
+    }
+
+    pub fn auctions_by_owner(deps: Deps, owner: String, start_after: Option<(String, u64)>, limit: Option<u32>) -> StdResult<AuctionsResp> {
+        let owner = deps.api.addr_validate(&owner)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_bound(deps, start_after)?;
+
+        let auctions = AUCTIONS
+            .idx
+            .owner
+            .prefix(owner.to_string())
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(auction_info))
+            .collect::<StdResult<_>>()?;
+
+        Ok(AuctionsResp { auctions })
+    }
+
+    pub fn auctions_by_status(deps: Deps, is_open: bool, start_after: Option<(String, u64)>, limit: Option<u32>) -> StdResult<AuctionsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_bound(deps, start_after)?;
+
+        let auctions = AUCTIONS
+            .idx
+            .status
+            .prefix(is_open as u8)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(auction_info))
+            .collect::<StdResult<_>>()?;
+
+        Ok(AuctionsResp { auctions })
+    }
+}
+
+pub mod exec {
+    use cosmwasm_std::{to_json_binary, Addr, DepsMut, Env, MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsg, WasmMsg};
+    use cw_utils::parse_reply_instantiate_data;
+
+    use crate::error::ContractError;
+    use crate::factory::state::{AuctionRecord, AUCTIONS, BIDDING_CODE_ID, PENDING_OWNER};
+    use crate::msg::{InstantiateMsg, MigrateMsg};
+
+    use super::INSTANTIATE_REPLY_ID;
+
+    fn is_registered(storage: &dyn Storage, address: &Addr) -> StdResult<bool> {
+        Ok(AUCTIONS
+            .prefix(address.clone())
+            .keys(storage, None, None, Order::Ascending)
+            .next()
+            .is_some())
+    }
+
+    pub fn create_auction(deps: DepsMut, env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+        let code_id = BIDDING_CODE_ID.load(deps.storage)?;
+
+        // The factory instantiates the contract, so the owner must be explicit
+        let owner = match &msg.owner {
+            Some(owner) => deps.api.addr_validate(owner)?,
+            None => info.sender.clone(),
+        };
+        PENDING_OWNER.save(deps.storage, &owner)?;
+
+        let label = format!("Bidding contract: {}", msg.commodity);
+        let msg = InstantiateMsg {
+            owner: Some(owner.to_string()),
+            registry: Some(env.contract.address.to_string()),
+            ..msg
+        };
+
+        // The owner must not be able to migrate the contract holding the bids, the factory admin does it through the factory
+        let instantiate = WasmMsg::Instantiate {
+            admin: Some(env.contract.address.to_string()),
+            code_id,
+            msg: to_json_binary(&msg)?,
+            funds: vec![],
+            label,
+        };
+
+        Ok(Response::new()
+            .add_submessage(SubMsg::reply_on_success(instantiate, INSTANTIATE_REPLY_ID))
+            .add_attribute("action", "create_auction")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("owner", owner.as_str()))
+    }
+
+    pub fn register_auction(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+        let resp = parse_reply_instantiate_data(msg)?;
+        let address = deps.api.addr_validate(&resp.contract_address)?;
+
+        let owner = PENDING_OWNER.load(deps.storage)?;
+        PENDING_OWNER.remove(deps.storage);
+
+        // A new contract holds a single open auction
+        AUCTIONS.save(deps.storage, (address.clone(), 1), &AuctionRecord {
+            owner: Some(owner.clone()),
+            is_open: true,
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register_auction")
+            .add_attribute("auction", address.as_str())
+            .add_attribute("owner", owner.as_str()))
+    }
+
+    pub fn update_auction(deps: DepsMut, info: MessageInfo, auction_id: u64, owner: Option<String>, is_open: bool) -> Result<Response, ContractError> {
+        if !is_registered(deps.storage, &info.sender)? {
+            return Err(ContractError::UnregisteredContract {});
+        }
+
+        let owner = owner.map(|owner| deps.api.addr_validate(&owner)).transpose()?;
+        AUCTIONS.save(deps.storage, (info.sender.clone(), auction_id), &AuctionRecord {
+            owner,
+            is_open,
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_auction")
+            .add_attribute("auction", info.sender.as_str())
+            .add_attribute("auction_id", auction_id.to_string()))
+    }
+
+    pub fn migrate_auction(deps: DepsMut, env: Env, info: MessageInfo, contract: String, code_id: u64) -> Result<Response, ContractError> {
+        // Whoever can migrate the factory can migrate the contracts it administers
+        let admin = deps.querier.query_wasm_contract_info(&env.contract.address)?.admin;
+        if admin.as_deref() != Some(info.sender.as_str()) {
+            return Err(ContractError::Unauthorized {
+                owner: admin.unwrap_or_default(),
+            });
+        }
+
+        let contract = deps.api.addr_validate(&contract)?;
+        if !is_registered(deps.storage, &contract)? {
+            return Err(ContractError::UnregisteredContract {});
+        }
+
+        let migrate = WasmMsg::Migrate {
+            contract_addr: contract.to_string(),
+            new_code_id: code_id,
+            msg: to_json_binary(&MigrateMsg {})?,
+        };
+
+        Ok(Response::new()
+            .add_message(migrate)
+            .add_attribute("action", "migrate_auction")
+            .add_attribute("auction", contract.as_str())
+            .add_attribute("code_id", code_id.to_string()))
+    }
+}
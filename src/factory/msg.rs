@@ -0,0 +1,69 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Code id of the bidding contract to instantiate.
+    pub bidding_code_id: u64,
+}
+
+#[cw_serde]
+pub enum ExecMsg {
+    /// Instantiates a bidding contract, owned by the sender unless an owner is given.
+    /// The factory is the admin of the contract and its registry, see `MigrateAuction`.
+    CreateAuction(Box<crate::msg::InstantiateMsg>),
+    /// Sent by registered contracts when an auction is created, closed or changes owner.
+    UpdateAuction {
+        auction_id: u64,
+        owner: Option<String>,
+        is_open: bool,
+    },
+    /// Migrates a registered contract to the given code, only by the admin of the factory.
+    MigrateAuction {
+        contract: String,
+        code_id: u64,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(ConfigResp)]
+    Config {},
+    /// Pages start after the given contract address and auction id.
+    #[returns(AuctionsResp)]
+    Auctions {
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+    },
+    #[returns(AuctionsResp)]
+    AuctionsByOwner {
+        owner: String,
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+    },
+    #[returns(AuctionsResp)]
+    AuctionsByStatus {
+        is_open: bool,
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct ConfigResp {
+    pub bidding_code_id: u64,
+}
+
+#[cw_serde]
+pub struct AuctionInfo {
+    pub address: String,
+    pub auction_id: u64,
+    /// Empty once ownership is renounced.
+    pub owner: Option<String>,
+    pub is_open: bool,
+}
+
+#[cw_serde]
+pub struct AuctionsResp {
+    pub auctions: Vec<AuctionInfo>,
+}
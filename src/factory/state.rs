@@ -0,0 +1,40 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
+use serde::{Deserialize, Serialize};
+
+pub const BIDDING_CODE_ID: Item<u64> = Item::new("bidding_code_id");
+// Owner of the auction being instantiated, until the reply registers it
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+
+// Auctions of the registered contracts, kept up to date by the contracts themselves
+pub const AUCTIONS: IndexedMap<(Addr, u64), AuctionRecord, AuctionIndexes> = IndexedMap::new("auctions", AuctionIndexes {
+    owner: MultiIndex::new(auction_owner, "auctions", "auctions__owner"),
+    status: MultiIndex::new(auction_status, "auctions", "auctions__status"),
+});
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuctionRecord {
+    /// Empty once ownership is renounced.
+    pub owner: Option<Addr>,
+    pub is_open: bool,
+}
+
+pub struct AuctionIndexes<'a> {
+    pub owner: MultiIndex<'a, String, AuctionRecord, (Addr, u64)>,
+    pub status: MultiIndex<'a, u8, AuctionRecord, (Addr, u64)>,
+}
+
+impl<'a> IndexList<AuctionRecord> for AuctionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AuctionRecord>> + '_> {
+        let v: Vec<&dyn Index<AuctionRecord>> = vec![&self.owner, &self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+fn auction_owner(_pk: &[u8], auction: &AuctionRecord) -> String {
+    auction.owner.as_ref().map_or_else(String::new, Addr::to_string)
+}
+
+fn auction_status(_pk: &[u8], auction: &AuctionRecord) -> u8 {
+    auction.is_open as u8
+}
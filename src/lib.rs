@@ -1,42 +1,76 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, to_binary};
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult, to_json_binary};
 use error::ContractError;
 
 mod contract;
 mod error;
+pub mod factory;
 pub mod msg;
 #[cfg(any(test, feature = "tests"))]
 pub mod multitest;
 mod state;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, msg: msg::InstantiateMsg) -> StdResult<Response> {
-    contract::instantiate(deps, info, msg.commodity, msg.bid_asset, msg.commission, msg.owner)
+pub fn instantiate(deps: DepsMut, env: Env, info: MessageInfo, msg: msg::InstantiateMsg) -> Result<Response, ContractError> {
+    contract::instantiate(deps, env, info, msg)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use msg::QueryMsg::*;
     use contract::query;
 
     match msg {
-        Auction {} => to_binary(&query::auction(deps)?),
-        Bids { address } => to_binary(&query::bids(deps, address)?),
-        HighestBid {} => to_binary(&query::highest_bid(deps)?),
-        Winner {} => to_binary(&query::winner(deps)?),
+        Auction { auction_id } => to_json_binary(&query::auction(deps, auction_id)?),
+        Auctions { start_after, limit } => to_json_binary(&query::auctions(deps, start_after, limit)?),
+        Bids { auction_id, address } => to_json_binary(&query::bids(deps, auction_id, address)?),
+        AllBids { auction_id, start_after, limit, order } => to_json_binary(&query::all_bids(deps, auction_id, start_after, limit, order)?),
+        BidHistory { auction_id, address, start_after, limit } => to_json_binary(&query::bid_history(deps, auction_id, address, start_after, limit)?),
+        History { auction_id, start_after, limit } => to_json_binary(&query::history(deps, auction_id, start_after, limit)?),
+        HighestBid { auction_id } => to_json_binary(&query::highest_bid(deps, auction_id)?),
+        Winner { auction_id } => to_json_binary(&query::winner(deps, auction_id)?),
+        Settlement { auction_id } => to_json_binary(&query::settlement(deps, auction_id)?),
+        MinNextBid { auction_id } => to_json_binary(&query::min_next_bid(deps, auction_id)?),
+        CurrentPrice { auction_id } => to_json_binary(&query::current_price(deps, env, auction_id)?),
+        Claimable { auction_id, address } => to_json_binary(&query::claimable(deps, auction_id, address)?),
+        Fees { auction_id } => to_json_binary(&query::fees(deps, auction_id)?),
+        Ownership { auction_id } => to_json_binary(&query::ownership(deps, auction_id)?),
+        Commitment { auction_id, address } => to_json_binary(&query::commitment(deps, auction_id, address)?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: msg::ExecMsg) -> Result<Response, ContractError> {
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: msg::ExecMsg) -> Result<Response, ContractError> {
     use contract::exec;
     use msg::ExecMsg::*;
 
     match msg {
-        Bid {} => exec::bid(deps, info),
-        Close {} => exec::close(deps, info),
-        Retract { receiver } => exec::retract(deps, info, receiver),
+        CreateAuction(msg) => exec::create_auction(deps, env, info, *msg),
+        ReceiveNft(msg) => exec::receive_nft(deps, env, info, msg),
+        Receive(msg) => exec::receive(deps, env, info, msg),
+        Bid { auction_id } => exec::bid(deps, env, info, auction_id),
+        ProxyBid { auction_id } => exec::proxy_bid(deps, env, info, auction_id),
+        Close { auction_id } => exec::close(deps, env, info, auction_id),
+        Retract { auction_id, receiver } => exec::retract(deps, info, auction_id, receiver),
+        Commit { auction_id, commitment } => exec::commit(deps, env, info, auction_id, commitment),
+        Cancel { auction_id, reason, refund_commission } => exec::cancel(deps, env, info, auction_id, reason, refund_commission),
+        Pause { auction_id } => exec::set_paused(deps, info, auction_id, true),
+        Unpause { auction_id } => exec::set_paused(deps, info, auction_id, false),
+        SetPauser { auction_id, pauser } => exec::set_pauser(deps, info, auction_id, pauser),
+        ProposeOwner { auction_id, new_owner, expiry } => exec::propose_owner(deps, env, info, auction_id, new_owner, expiry),
+        AcceptOwnership { auction_id } => exec::accept_ownership(deps, env, info, auction_id),
+        RenounceOwnership { auction_id } => exec::renounce_ownership(deps, info, auction_id),
+        Reveal { auction_id, amount, salt } => exec::reveal(deps, env, info, auction_id, amount, salt),
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    contract::reply(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: msg::MigrateMsg) -> Result<Response, ContractError> {
+    contract::migrate(deps, env, msg)
+}
\ No newline at end of file
@@ -1,48 +1,464 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Order, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::Expiration;
+use cw721::Cw721ReceiveMsg;
+use sha2::{Digest, Sha256};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub commodity: String,
+    /// Denomination of bids, the amount is the reserve price.
     pub bid_asset: Coin,
-    pub commission: Uint128,
+    /// Charged on every bid and paid to the fee recipients, as set by the commission policy.
+    pub commission: Commission,
+    /// Defaults to paying the commission on every bid.
+    pub commission_policy: Option<CommissionPolicy>,
+    /// Splits the commission, shares must add up to 100%. Defaults to the owner.
+    pub fee_recipients: Option<Vec<FeeRecipient>>,
     pub owner: Option<String>,
+    /// Bidding opens at this time, defaults to the time of instantiation.
+    pub start_time: Option<Timestamp>,
+    /// Bidding ends at this time, after which anyone can close the auction.
+    /// Without an end time only the owner can close it.
+    pub end_time: Option<Timestamp>,
+    /// Extends the end time when bids are placed right before it, requires an end time.
+    pub soft_close: Option<SoftClose>,
+    /// Keeps the reserve price out of query responses.
+    #[serde(default)]
+    pub hidden_reserve: bool,
+    /// Minimum raise over the highest bid, bids must always exceed it by at least one unit.
+    pub min_increment: Option<BidIncrement>,
+    /// A bid of at least this amount wins at this price and closes the auction immediately, the rest is refunded.
+    /// Not with second-price settlement.
+    pub buy_now: Option<Uint128>,
+    /// Defaults to open bidding.
+    pub mode: Option<AuctionMode>,
+    /// Vickrey settlement, the winner pays the runner-up bid and can retract the rest.
+    /// The commission of the leader is held and charged on the price it pays.
+    #[serde(default)]
+    pub second_price: bool,
+    /// The denom of the bid asset is the address of a cw20 token, bids are sent with its `Send` message.
+    #[serde(default)]
+    pub cw20: bool,
+    /// Can pause the auction along with the owner.
+    pub pauser: Option<String>,
+    /// Factory told about new auctions and changes to their owner or status, set by the factory.
+    /// Only read when instantiating the contract.
+    pub registry: Option<String>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+pub enum Commission {
+    /// Share of each bid, at most 100%.
+    Rate { rate: Decimal },
+    /// Fixed fee per bid.
+    Flat { amount: Uint128 },
+    /// Rate of the highest tier reached by the total bid of the bidder, tiers in ascending order.
+    Tiered { tiers: Vec<CommissionTier> },
+}
+
+#[cw_serde]
+pub enum CommissionPolicy {
+    /// Commission is paid on every bid and not refunded.
+    Upfront {},
+    /// Commission is held by the contract and only charged to the winner on close.
+    Escrowed {},
+}
+
+#[cw_serde]
+pub struct FeeRecipient {
+    pub address: String,
+    pub share: Decimal,
+}
+
+#[cw_serde]
+pub struct CommissionTier {
+    pub min_bid: Uint128,
+    pub rate: Decimal,
+}
+
+#[cw_serde]
+pub enum AuctionMode {
+    Open {},
+    /// Bids are committed until the end time and revealed within the reveal period after it.
+    Sealed {
+        reveal_period: u64,
+        /// Unrevealed deposits are paid to the owner on close instead of being retractable.
+        forfeit_unrevealed: bool,
+    },
+    /// Price declines from the start time to the floor price, the first bid paying it wins.
+    Dutch {
+        start_price: Uint128,
+        floor_price: Uint128,
+        /// Seconds until the floor price is reached.
+        decline_period: u64,
+        /// Lowers the price every this many seconds instead of linearly.
+        step: Option<u64>,
+    },
+}
+
+#[cw_serde]
+pub struct SoftClose {
+    /// Bids placed within this many seconds of the end time extend it.
+    pub window: u64,
+    /// Seconds added to the end time on each extension.
+    pub extension: u64,
+}
+
+#[cw_serde]
+pub enum BidIncrement {
+    Absolute {
+        amount: Uint128,
+    },
+    /// Whole-number percentage of the highest bid, at most 100.
+    Percentage {
+        percent: Uint128,
+    },
 }
 
 #[cw_serde]
 pub enum ExecMsg {
-    Bid {},
-    Close {},
+    /// Creates another auction, owned by the sender unless an owner is given.
+    CreateAuction(Box<InstantiateMsg>),
+    /// Creates an auction for the received NFT, the message is the `InstantiateMsg` of the auction.
+    /// The sender of the NFT owns the auction and the NFT is held in escrow until it closes.
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Bids paid in the cw20 token of the auction, the message is a `ReceiveMsg`.
+    Receive(Cw20ReceiveMsg),
+    Bid {
+        auction_id: u64,
+    },
+    /// Deposits a hidden maximum, the contract bids on behalf of the sender by the minimum increment
+    /// whenever it is outbid, up to the maximum.
+    ProxyBid {
+        auction_id: u64,
+    },
+    Close {
+        auction_id: u64,
+    },
     Retract {
+        auction_id: u64,
         receiver: Option<String>,
     },
+    /// Commits to a sealed bid, the funds sent are a deposit that must cover the bid.
+    Commit {
+        auction_id: u64,
+        commitment: Binary,
+    },
+    /// Ends the auction without a winner, all bids can be retracted in full. Only before the end time.
+    Cancel {
+        auction_id: u64,
+        reason: Option<String>,
+        /// Commission already paid out must be sent along to refund it, escrowed commission is always
+        /// refunded. Auctions in a cw20 token refund it through `ReceiveMsg::Cancel`.
+        #[serde(default)]
+        refund_commission: bool,
+    },
+    /// Freezes bidding, revealing, closing and retracting until unpaused, by the owner or the pauser.
+    Pause {
+        auction_id: u64,
+    },
+    Unpause {
+        auction_id: u64,
+    },
+    /// Replaces the pauser, by the owner.
+    SetPauser {
+        auction_id: u64,
+        pauser: Option<String>,
+    },
+    /// Proposes a new owner, who takes over the auction and its proceeds once accepting.
+    /// Replaces any earlier proposal.
+    ProposeOwner {
+        auction_id: u64,
+        new_owner: String,
+        /// Defaults to never expiring.
+        expiry: Option<Expiration>,
+    },
+    AcceptOwnership {
+        auction_id: u64,
+    },
+    /// Leaves a closed auction without an owner.
+    RenounceOwnership {
+        auction_id: u64,
+    },
+    /// Reveals a committed bid, any deposit above the amount is refunded.
+    Reveal {
+        auction_id: u64,
+        amount: Uint128,
+        salt: String,
+    },
+}
+
+/// Messages sent along with cw20 tokens, the tokens take the place of the funds.
+#[cw_serde]
+pub enum ReceiveMsg {
+    Bid {
+        auction_id: u64,
+    },
+    ProxyBid {
+        auction_id: u64,
+    },
+    Commit {
+        auction_id: u64,
+        commitment: Binary,
+    },
+    /// Cancels refunding the commission already paid out, the tokens sent must match it.
+    Cancel {
+        auction_id: u64,
+        reason: Option<String>,
+    },
+}
+
+#[cw_serde]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
+    }
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(AuctionResp)]
-    Auction {},
-    #[returns(Coin)]
+    Auction {
+        auction_id: u64,
+    },
+    #[returns(AuctionsResp)]
+    Auctions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(BidResp)]
     Bids {
+        auction_id: u64,
         address: String,
     },
+    /// Bids ordered by gross amount, highest first unless ascending.
+    /// Pages start after the bid of the given address.
+    #[returns(AllBidsResp)]
+    AllBids {
+        auction_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    /// Bids of the address in the order placed, including top-ups.
+    #[returns(BidHistoryResp)]
+    BidHistory {
+        auction_id: u64,
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Bids of all bidders in the order placed.
+    #[returns(BidHistoryResp)]
+    History {
+        auction_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The bid is the current price, never the maximum of a proxy bid.
     #[returns(BidResp)]
-    HighestBid {},
-    #[returns(BidResp)]
-    Winner {},
+    HighestBid {
+        auction_id: u64,
+    },
+    #[returns(WinnerResp)]
+    Winner {
+        auction_id: u64,
+    },
+    /// Report of a closed auction, fails while it is open.
+    #[returns(SettlementResp)]
+    Settlement {
+        auction_id: u64,
+    },
+    #[returns(Coin)]
+    MinNextBid {
+        auction_id: u64,
+    },
+    #[returns(Coin)]
+    CurrentPrice {
+        auction_id: u64,
+    },
+    /// Funds the address can retract, zero once retracted.
+    #[returns(Coin)]
+    Claimable {
+        auction_id: u64,
+        address: String,
+    },
+    /// Commission paid to each fee recipient.
+    #[returns(FeesResp)]
+    Fees {
+        auction_id: u64,
+    },
+    #[returns(OwnershipResp)]
+    Ownership {
+        auction_id: u64,
+    },
+    #[returns(CommitmentResp)]
+    Commitment {
+        auction_id: u64,
+        address: String,
+    },
+}
+
+#[cw_serde]
+pub struct Nft {
+    pub contract: Addr,
+    pub token_id: String,
 }
 
 #[cw_serde]
 pub struct AuctionResp {
+    pub auction_id: u64,
+    /// Empty once ownership is renounced.
+    pub owner: Option<String>,
     pub commodity: String,
+    /// The amount is zero when the reserve price is hidden.
     pub bid_asset: Coin,
-    pub commission: Uint128,
+    pub commission: Commission,
+    pub commission_policy: CommissionPolicy,
     pub is_open: bool,
+    pub start_time: Timestamp,
+    /// Effective end time, including any soft close extensions.
+    pub end_time: Option<Timestamp>,
+    pub soft_close: Option<SoftClose>,
+    pub extensions: u32,
+    pub hidden_reserve: bool,
+    pub reserve_met: bool,
+    pub min_increment: Option<BidIncrement>,
+    pub buy_now: Option<Uint128>,
+    pub mode: AuctionMode,
+    pub second_price: bool,
+    pub cw20: bool,
+    /// NFT held in escrow, delivered to the winner on close.
+    pub nft: Option<Nft>,
+    pub cancellation: Option<Cancellation>,
+    pub paused: bool,
+    pub pauser: Option<String>,
+}
+
+#[cw_serde]
+pub struct Cancellation {
+    pub reason: Option<String>,
+    /// Bidders retract their commission along with the bid.
+    pub refund_commission: bool,
+}
+
+#[cw_serde]
+pub struct AuctionsResp {
+    pub auctions: Vec<AuctionResp>,
 }
 
 #[cw_serde]
 pub struct BidResp {
     pub address: String,
+    /// Gross amount deposited, bids are ranked by it.
+    pub bid: Coin,
+    pub commission: Coin,
+    /// Escrowed after commission.
+    pub net: Coin,
+}
+
+#[cw_serde]
+pub struct AllBidsResp {
+    pub bids: Vec<BidResp>,
+    /// Number of bidders in the auction.
+    pub count: u64,
+    /// Bid funds still held by the contract, unrevealed sealed bid deposits excluded.
+    pub escrowed: Coin,
+}
+
+#[cw_serde]
+pub struct BidEventResp {
+    /// Position of the bid in the auction history, pages start after it.
+    pub event_id: u64,
+    pub address: String,
+    pub height: u64,
+    pub time: Timestamp,
+    /// Gross amount of this bid.
     pub bid: Coin,
+    pub commission: Uint128,
+    /// Gross amount of the bidder after this bid.
+    pub total: Coin,
+}
+
+#[cw_serde]
+pub struct BidHistoryResp {
+    pub events: Vec<BidEventResp>,
+}
+
+#[cw_serde]
+pub struct WinnerResp {
+    pub address: String,
+    pub bid: Coin,
+    /// Price the winner pays, below the winning bid in a second-price auction.
+    pub clearing_price: Coin,
+    /// Auction closed without a sale, no bids or the reserve price was not met.
+    pub unsold: bool,
+}
+
+#[cw_serde]
+pub enum Outcome {
+    Sold {},
+    /// No bids or the reserve price was not met.
+    Unsold {},
+    /// Closed by the owner, all bids are refunded.
+    Cancelled {},
+}
+
+#[cw_serde]
+pub struct SettlementResp {
+    /// Empty unless sold.
+    pub winner: Option<String>,
+    pub clearing_price: Coin,
+    /// Commission paid to the fee recipients over the whole auction.
+    pub commission: Coin,
+    /// Paid to the owner on close, the winning bid and any forfeited deposits.
+    pub proceeds: Coin,
+    pub height: u64,
+    pub time: Timestamp,
+    pub outcome: Outcome,
+}
+
+#[cw_serde]
+pub struct FeeResp {
+    pub address: String,
+    pub amount: Coin,
+}
+
+#[cw_serde]
+pub struct FeesResp {
+    pub fees: Vec<FeeResp>,
+}
+
+#[cw_serde]
+pub struct OwnershipResp {
+    /// Empty once ownership is renounced.
+    pub owner: Option<String>,
+    pub pending_owner: Option<String>,
+    pub pending_expiry: Option<Expiration>,
+}
+
+#[cw_serde]
+pub struct CommitmentResp {
+    /// Empty when no unrevealed bid is committed.
+    pub commitment: Option<Binary>,
+    pub deposit: Coin,
+}
+
+/// Commitment to a sealed bid, the bidder address prevents copying another bidder's commitment.
+pub fn sealed_bid_commitment(bidder: &str, amount: Uint128, salt: &str) -> Binary {
+    Binary::from(Sha256::digest(format!("{bidder}:{amount}:{salt}")).as_slice())
 }
\ No newline at end of file
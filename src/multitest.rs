@@ -1,4 +1,5 @@
 pub mod contract;
+pub mod factory;
 
 #[cfg(test)]
 mod tests;
\ No newline at end of file
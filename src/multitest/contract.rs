@@ -1,12 +1,16 @@
 use cw_multi_test::{Executor, ContractWrapper};
 use cw_multi_test::App;
-use cosmwasm_std::{Addr, Coin, StdResult, Uint128};
+use cosmwasm_std::{to_json_binary, Addr, Binary, Coin, StdResult, Uint128};
+use cw20::Cw20ExecuteMsg;
+use cw721::Cw721ExecuteMsg;
+use cw_utils::Expiration;
 
-use crate::{execute, instantiate, query};
+use crate::{execute, instantiate, migrate, query, reply};
 use crate::error::ContractError;
-use crate::msg::{InstantiateMsg, ExecMsg, QueryMsg};
-use crate::msg::{AuctionResp, BidResp};
+use crate::msg::{Commission, InstantiateMsg, ExecMsg, MigrateMsg, QueryMsg, ReceiveMsg};
+use crate::msg::{AllBidsResp, AuctionResp, AuctionsResp, BidHistoryResp, BidResp, CommitmentResp, FeesResp, OwnershipResp, SettlementResp, SortOrder, WinnerResp};
 
+#[derive(Debug)]
 pub struct BiddingContract(Addr);
 
 impl From<BiddingContract> for Addr {
@@ -20,49 +24,168 @@ impl BiddingContract {
         &self.0
     }
 
+    pub fn from_addr(addr: Addr) -> Self {
+        BiddingContract(addr)
+    }
+
     pub fn store_code(app: &mut App) -> u64 {
-        let contract = ContractWrapper::new(execute, instantiate, query);
+        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply).with_migrate(migrate);
         app.store_code(Box::new(contract))
     }
 
     #[track_caller]
-    pub fn instantiate<'a>(app: &mut App, code_id: u64, sender: &Addr, label: &str, admin: impl Into<Option<&'a Addr>>, commodity: &str, bid_asset: Coin, commission: Uint128) -> StdResult<BiddingContract> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn instantiate<'a>(app: &mut App, code_id: u64, sender: &Addr, label: &str, admin: impl Into<Option<&'a Addr>>, commodity: &str, bid_asset: Coin, commission: Commission) -> Result<BiddingContract, ContractError> {
         let admin = admin.into();
-        
-        app.instantiate_contract(
+
+        Self::instantiate_with_msg(
+            app,
             code_id,
-            sender.clone(),
+            sender,
+            label,
+            admin,
             &InstantiateMsg {
                 commodity: commodity.to_string(),
                 bid_asset,
                 commission,
+                commission_policy: None,
+                fee_recipients: None,
                 owner: admin.map(Addr::to_string),
+                start_time: None,
+                end_time: None,
+                soft_close: None,
+                hidden_reserve: false,
+                min_increment: None,
+                buy_now: None,
+                mode: None,
+                second_price: false,
+                cw20: false,
+                pauser: None,
+                registry: None,
             },
+        )
+    }
+
+    #[track_caller]
+    pub fn instantiate_with_msg<'a>(app: &mut App, code_id: u64, sender: &Addr, label: &str, admin: impl Into<Option<&'a Addr>>, msg: &InstantiateMsg) -> Result<BiddingContract, ContractError> {
+        let admin = admin.into();
+
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            msg,
             &[],
             label,
             admin.map(Addr::to_string),
         )
         .map(BiddingContract)
-        .map_err(|err| err.downcast().unwrap())
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
     }
 
-    pub fn bid(&self, app: &mut App, sender: &Addr, amount: &[Coin]) -> Result<(), ContractError> {
+    #[track_caller]
+    pub fn migrate(&self, app: &mut App, sender: &Addr, code_id: u64) -> Result<(), ContractError> {
+        app.migrate_contract(
+            sender.clone(),
+            self.0.clone(),
+            &MigrateMsg {},
+            code_id,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn create_auction(&self, app: &mut App, sender: &Addr, msg: InstantiateMsg) -> Result<u64, ContractError> {
+        let resp = app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::CreateAuction(Box::new(msg)),
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        let auction_id = resp
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "auction_id")
+            .map(|attr| attr.value.parse().unwrap())
+            .unwrap();
+
+        Ok(auction_id)
+    }
+
+    /// Sends the NFT to the contract to auction it, returning the auction id.
+    pub fn create_nft_auction(&self, app: &mut App, sender: &Addr, nft_contract: &Addr, token_id: &str, msg: &InstantiateMsg) -> Result<u64, ContractError> {
+        let resp = app.execute_contract(
+            sender.clone(),
+            nft_contract.clone(),
+            &Cw721ExecuteMsg::SendNft {
+                contract: self.0.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(msg).unwrap(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        let auction_id = resp
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "auction_id")
+            .map(|attr| attr.value.parse().unwrap())
+            .unwrap();
+
+        Ok(auction_id)
+    }
+
+    pub fn bid(&self, app: &mut App, auction_id: u64, sender: &Addr, amount: &[Coin]) -> Result<(), ContractError> {
         app.execute_contract(
             sender.clone(),
             self.0.clone(),
-            &ExecMsg::Bid {},
-            &amount,
+            &ExecMsg::Bid { auction_id },
+            amount,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn proxy_bid(&self, app: &mut App, auction_id: u64, sender: &Addr, max: &[Coin]) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProxyBid { auction_id },
+            max,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    /// Bids by sending cw20 tokens to the contract.
+    pub fn bid_cw20(&self, app: &mut App, auction_id: u64, sender: &Addr, token: &Addr, amount: u128) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::Bid { auction_id }).unwrap(),
+            },
+            &[],
         )
         .map_err(|err| err.downcast::<ContractError>().unwrap())?;
 
         Ok(())
     }
 
-    pub fn close(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+    pub fn close(&self, app: &mut App, auction_id: u64, sender: &Addr) -> Result<(), ContractError> {
         app.execute_contract(
             sender.clone(),
             self.0.clone(),
-            &ExecMsg::Close {},
+            &ExecMsg::Close { auction_id },
             &[],
         )
         .map_err(|err| err.downcast::<ContractError>().unwrap())?;
@@ -70,11 +193,12 @@ impl BiddingContract {
         Ok(())
     }
 
-    pub fn retract(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+    pub fn retract(&self, app: &mut App, auction_id: u64, sender: &Addr) -> Result<(), ContractError> {
         app.execute_contract(
             sender.clone(),
             self.0.clone(),
             &ExecMsg::Retract {
+                auction_id,
                 receiver: Some(sender.to_string()),
             },
             &[],
@@ -84,19 +208,234 @@ impl BiddingContract {
         Ok(())
     }
 
-    pub fn query_auction(&self, app: &App) -> StdResult<AuctionResp> {
+    pub fn retract_to(&self, app: &mut App, auction_id: u64, sender: &Addr, receiver: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Retract {
+                auction_id,
+                receiver: Some(receiver.to_string()),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn commit(&self, app: &mut App, auction_id: u64, sender: &Addr, commitment: Binary, deposit: &[Coin]) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Commit { auction_id, commitment },
+            deposit,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn reveal(&self, app: &mut App, auction_id: u64, sender: &Addr, amount: u128, salt: &str) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Reveal {
+                auction_id,
+                amount: Uint128::new(amount),
+                salt: salt.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn cancel(&self, app: &mut App, auction_id: u64, sender: &Addr, reason: Option<&str>, refund_commission: bool, funds: &[Coin]) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Cancel {
+                auction_id,
+                reason: reason.map(str::to_string),
+                refund_commission,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    /// Cancels by sending the cw20 commission refund to the contract.
+    pub fn cancel_cw20(&self, app: &mut App, auction_id: u64, sender: &Addr, token: &Addr, amount: u128) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::Cancel { auction_id, reason: None }).unwrap(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn pause(&self, app: &mut App, auction_id: u64, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Pause { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn unpause(&self, app: &mut App, auction_id: u64, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Unpause { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn set_pauser(&self, app: &mut App, auction_id: u64, sender: &Addr, pauser: Option<&Addr>) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::SetPauser {
+                auction_id,
+                pauser: pauser.map(Addr::to_string),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn propose_owner(&self, app: &mut App, auction_id: u64, sender: &Addr, new_owner: &Addr, expiry: Option<Expiration>) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProposeOwner {
+                auction_id,
+                new_owner: new_owner.to_string(),
+                expiry,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn accept_ownership(&self, app: &mut App, auction_id: u64, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::AcceptOwnership { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn renounce_ownership(&self, app: &mut App, auction_id: u64, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::RenounceOwnership { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn query_auction(&self, app: &App, auction_id: u64) -> StdResult<AuctionResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Auction { auction_id })
+    }
+
+    pub fn query_address(&self, app: &App, auction_id: u64, address: &Addr) -> StdResult<BidResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Bids { auction_id, address: address.to_string() })
+    }
+
+    pub fn query_all_bids(&self, app: &App, auction_id: u64, start_after: Option<&Addr>, limit: Option<u32>, order: Option<SortOrder>) -> StdResult<AllBidsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::AllBids { auction_id, start_after: start_after.map(Addr::to_string), limit, order })
+    }
+
+    pub fn query_bid_history(&self, app: &App, auction_id: u64, address: &Addr, start_after: Option<u64>, limit: Option<u32>) -> StdResult<BidHistoryResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::BidHistory { auction_id, address: address.to_string(), start_after, limit })
+    }
+
+    pub fn query_history(&self, app: &App, auction_id: u64, start_after: Option<u64>, limit: Option<u32>) -> StdResult<BidHistoryResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::History { auction_id, start_after, limit })
+    }
+
+    pub fn query_highest_bid(&self, app: &App, auction_id: u64) -> StdResult<BidResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::HighestBid { auction_id })
+    }
+
+    pub fn query_winner(&self, app: &App, auction_id: u64) -> StdResult<WinnerResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Winner { auction_id })
+    }
+
+    pub fn query_auctions(&self, app: &App, start_after: Option<u64>, limit: Option<u32>) -> StdResult<AuctionsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Auctions { start_after, limit })
+    }
+
+    pub fn query_settlement(&self, app: &App, auction_id: u64) -> StdResult<SettlementResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Settlement { auction_id })
+    }
+
+    pub fn query_min_next_bid(&self, app: &App, auction_id: u64) -> StdResult<Coin> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::MinNextBid { auction_id })
+    }
+
+    pub fn query_current_price(&self, app: &App, auction_id: u64) -> StdResult<Coin> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::CurrentPrice { auction_id })
+    }
+
+    pub fn query_claimable(&self, app: &App, auction_id: u64, address: &Addr) -> StdResult<Coin> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Claimable { auction_id, address: address.to_string() })
+    }
+
+    pub fn query_fees(&self, app: &App, auction_id: u64) -> StdResult<FeesResp> {
         app.wrap()
-            .query_wasm_smart(self.0.clone(), &QueryMsg::Auction {})
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Fees { auction_id })
     }
 
-    pub fn query_address(&self, app: &App, address: &Addr) -> StdResult<Coin> {
+    pub fn query_ownership(&self, app: &App, auction_id: u64) -> StdResult<OwnershipResp> {
         app.wrap()
-            .query_wasm_smart(self.0.clone(), &QueryMsg::Bids { address: address.to_string() })
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Ownership { auction_id })
     }
 
-    pub fn query_highest_bid(&self, app: &App) -> StdResult<BidResp> {
+    pub fn query_commitment(&self, app: &App, auction_id: u64, address: &Addr) -> StdResult<CommitmentResp> {
         app.wrap()
-            .query_wasm_smart(self.0.clone(), &QueryMsg::HighestBid {})
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Commitment { auction_id, address: address.to_string() })
     }
 }
 
@@ -0,0 +1,90 @@
+use cw_multi_test::{Executor, ContractWrapper};
+use cw_multi_test::App;
+use cosmwasm_std::{Addr, StdResult};
+
+use crate::factory::{execute, instantiate, query, reply};
+use crate::error::ContractError;
+use crate::factory::msg::{AuctionsResp, ExecMsg, InstantiateMsg, QueryMsg};
+
+#[derive(Debug)]
+pub struct FactoryContract(Addr);
+
+impl FactoryContract {
+    pub fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    pub fn store_code(app: &mut App) -> u64 {
+        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        app.store_code(Box::new(contract))
+    }
+
+    #[track_caller]
+    pub fn instantiate<'a>(app: &mut App, code_id: u64, sender: &Addr, label: &str, admin: impl Into<Option<&'a Addr>>, bidding_code_id: u64) -> StdResult<FactoryContract> {
+        let admin = admin.into();
+
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            &InstantiateMsg { bidding_code_id },
+            &[],
+            label,
+            admin.map(Addr::to_string),
+        )
+        .map(FactoryContract)
+        .map_err(|err| err.downcast().unwrap())
+    }
+
+    /// Returns the address of the instantiated bidding contract.
+    pub fn create_auction(&self, app: &mut App, sender: &Addr, msg: crate::msg::InstantiateMsg) -> Result<Addr, ContractError> {
+        let resp = app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::CreateAuction(Box::new(msg)),
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        let address = resp
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "auction")
+            .map(|attr| Addr::unchecked(&attr.value))
+            .unwrap();
+
+        Ok(address)
+    }
+
+    pub fn migrate_auction(&self, app: &mut App, sender: &Addr, contract: &Addr, code_id: u64) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::MigrateAuction {
+                contract: contract.to_string(),
+                code_id,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn query_auctions(&self, app: &App, start_after: Option<(&Addr, u64)>, limit: Option<u32>) -> StdResult<AuctionsResp> {
+        let start_after = start_after.map(|(address, auction_id)| (address.to_string(), auction_id));
+
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Auctions { start_after, limit })
+    }
+
+    pub fn query_auctions_by_owner(&self, app: &App, owner: &Addr) -> StdResult<AuctionsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::AuctionsByOwner { owner: owner.to_string(), start_after: None, limit: None })
+    }
+
+    pub fn query_auctions_by_status(&self, app: &App, is_open: bool) -> StdResult<AuctionsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::AuctionsByStatus { is_open, start_after: None, limit: None })
+    }
+}
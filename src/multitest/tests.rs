@@ -1,12 +1,44 @@
-use cosmwasm_std::{Addr, Coin, coins, Uint128};
-use cw_multi_test::App;
+use cosmwasm_std::{coin, coins, to_json_binary, Addr, Coin, Decimal, DepsMut, Empty, Env, MessageInfo, Response, StdResult, Uint128};
+use cw2::ContractVersion;
+use cw_utils::Expiration;
+use cw_multi_test::{App, ContractWrapper, Executor};
+use cw20::{BalanceResponse, Cw20Coin, Cw20QueryMsg};
+use cw721::{Cw721QueryMsg, Cw721ReceiveMsg, OwnerOfResponse};
 
 use crate::error::ContractError;
-use crate::msg::{AuctionResp, BidResp};
+use crate::factory::msg::AuctionInfo;
+use crate::msg::{sealed_bid_commitment, AuctionMode, AuctionResp, BidIncrement, BidResp, Cancellation, Commission, CommissionPolicy, CommissionTier, ExecMsg, FeeRecipient, FeeResp, InstantiateMsg, Nft, Outcome, OwnershipResp, SettlementResp, SoftClose, SortOrder, WinnerResp};
 
 use super::contract::BiddingContract;
+use super::factory::FactoryContract;
 
 const ATOM: &str = "atom";
+const AUCTION_ID: u64 = 1;
+
+fn instantiate_msg(owner: &Addr) -> InstantiateMsg {
+    InstantiateMsg {
+        commodity: "Item".to_string(),
+        bid_asset: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(0),
+        },
+        commission: Commission::Rate { rate: Decimal::percent(10) },
+        commission_policy: None,
+        fee_recipients: None,
+        owner: Some(owner.to_string()),
+        start_time: None,
+        end_time: None,
+        soft_close: None,
+        hidden_reserve: false,
+        min_increment: None,
+        buy_now: None,
+        mode: None,
+        second_price: false,
+        cw20: false,
+        pauser: None,
+        registry: None,
+    }
+}
 
 #[test]
 fn query_open_auction() {
@@ -25,19 +57,37 @@ fn query_open_auction() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
     ).unwrap();
 
-    let resp = BiddingContract::query_auction(&contract, &app).unwrap();
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
 
     assert_eq!(resp, AuctionResp {
+        auction_id: AUCTION_ID,
+        owner: Some("sender".to_string()),
         commodity:"Item".to_string(),
         bid_asset: Coin {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        commission: Uint128::new(10),
-        is_open: true, 
+        commission: Commission::Rate { rate: Decimal::percent(10) },
+        commission_policy: CommissionPolicy::Upfront {},
+        is_open: true,
+        start_time: app.block_info().time,
+        end_time: None,
+        soft_close: None,
+        extensions: 0,
+        hidden_reserve: false,
+        reserve_met: false,
+        min_increment: None,
+        buy_now: None,
+        mode: AuctionMode::Open {},
+        second_price: false,
+        cw20: false,
+        nft: None,
+        cancellation: None,
+        paused: false,
+        pauser: None,
     });
 }
 
@@ -59,20 +109,38 @@ fn query_closed_auction() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
     ).unwrap();
 
-    BiddingContract::close(&contract, &mut app, &owner).unwrap();
-    let resp = BiddingContract::query_auction(&contract, &app).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
 
     assert_eq!(resp, AuctionResp {
+        auction_id: AUCTION_ID,
+        owner: Some("owner".to_string()),
         commodity:"Item".to_string(),
         bid_asset: Coin {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        commission: Uint128::new(10),
-        is_open: false, 
+        commission: Commission::Rate { rate: Decimal::percent(10) },
+        commission_policy: CommissionPolicy::Upfront {},
+        is_open: false,
+        start_time: app.block_info().time,
+        end_time: None,
+        soft_close: None,
+        extensions: 0,
+        hidden_reserve: false,
+        reserve_met: false,
+        min_increment: None,
+        buy_now: None,
+        mode: AuctionMode::Open {},
+        second_price: false,
+        cw20: false,
+        nft: None,
+        cancellation: None,
+        paused: false,
+        pauser: None,
     });
 }
 
@@ -101,11 +169,11 @@ fn owner_can_not_bid() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
     ).unwrap();
 
     // Expecting error
-    let err = BiddingContract::bid(&contract, &mut app, &owner, &coins(10, ATOM)).unwrap_err();
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &owner, &coins(10, ATOM)).unwrap_err();
 
     assert_eq!(err, ContractError::BiddingByOwner {});
 }
@@ -136,13 +204,13 @@ fn bid_closed_auction() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
     ).unwrap();
 
-    BiddingContract::close(&contract, &mut app, &owner).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
 
     // Expecting error
-    let err = BiddingContract::bid(&contract, &mut app, &bidder, &coins(10, ATOM)).unwrap_err();
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap_err();
 
     assert_eq!(err, ContractError::BiddingClosed {});
 }
@@ -173,16 +241,69 @@ fn query_bids_by_address() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(20, ATOM)).unwrap();
+    let resp = BiddingContract::query_address(&contract, &app, AUCTION_ID, &bidder).unwrap();
+
+    assert_eq!(resp, BidResp {
+        address: bidder.to_string(),
+        bid: coins(20, ATOM)[0].clone(),
+        commission: coins(2, ATOM)[0].clone(),
+        net: coins(18, ATOM)[0].clone(),
+    });
+}
+
+#[test]
+fn top_up_ranks_by_gross_amount() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder_0 = Addr::unchecked("bidder_0");
+    let bidder_1 = Addr::unchecked("bidder_1");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder_0, coins(20, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder_1, coins(15, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
     ).unwrap();
 
-    BiddingContract::bid(&contract, &mut app, &bidder, &coins(20, ATOM)).unwrap();
-    let resp = BiddingContract::query_address(&contract, &app, &bidder).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_1, &coins(15, ATOM)).unwrap();
 
-    assert_eq!(resp, Coin {
-        denom: ATOM.to_string(),
-        amount: Uint128::new(18),
+    // The top-up counts together with the earlier bid
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(10, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, BidResp {
+        address: bidder_0.to_string(),
+        bid: coins(20, ATOM)[0].clone(),
+        commission: coins(2, ATOM)[0].clone(),
+        net: coins(18, ATOM)[0].clone(),
     });
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+
+    // Commission of every bid and the net amount of the winning bid
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(21, ATOM));
+    assert_eq!(BiddingContract::query_address(&contract, &app, AUCTION_ID, &bidder_0).unwrap().net, coins(0, ATOM)[0]);
 }
 
 #[test]
@@ -217,13 +338,13 @@ fn query_highest_bid() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
     ).unwrap();
 
-    BiddingContract::bid(&contract, &mut app, &bidder_0, &coins(10, ATOM)).unwrap();
-    BiddingContract::bid(&contract, &mut app, &bidder_1, &coins(20, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_1, &coins(20, ATOM)).unwrap();
 
-    let resp = BiddingContract::query_highest_bid(&contract, &app).unwrap();
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
 
     assert_eq!(resp, BidResp {
         address: bidder_1.to_string(),
@@ -231,6 +352,14 @@ fn query_highest_bid() {
             denom: ATOM.to_string(),
             amount: Uint128::new(20),
         },
+        commission: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(2),
+        },
+        net: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(18),
+        },
     });
 }
 
@@ -266,14 +395,14 @@ fn retract_by_winner() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
     ).unwrap();
 
-    BiddingContract::bid(&contract, &mut app, &bidder, &coins(10, ATOM)).unwrap();
-    BiddingContract::bid(&contract, &mut app, &winner, &coins(20, ATOM)).unwrap();
-    BiddingContract::close(&contract, &mut app, &owner).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(20, ATOM)).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
     // Expecting error
-    let err = BiddingContract::retract(&contract, &mut app, &winner).unwrap_err();
+    let err = BiddingContract::retract(&contract, &mut app, AUCTION_ID, &winner).unwrap_err();
 
     assert_eq!(err, ContractError::RetractByWinner {});
 }
@@ -310,13 +439,2452 @@ fn retract_by_bidder() {
             denom: ATOM.to_string(),
             amount: Uint128::new(0),
         },
-        Uint128::new(10),
+        Commission::Rate { rate: Decimal::percent(10) },
     ).unwrap();
 
-    BiddingContract::bid(&contract, &mut app, &bidder, &coins(10, ATOM)).unwrap();
-    BiddingContract::bid(&contract, &mut app, &winner, &coins(20, ATOM)).unwrap();
-    BiddingContract::close(&contract, &mut app, &owner).unwrap();
-    BiddingContract::retract(&contract, &mut app, &bidder).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(20, ATOM)).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
 
     assert_eq!(app.wrap().query_all_balances(bidder).unwrap(), coins(9, ATOM));
-}
\ No newline at end of file
+}
+
+#[test]
+fn retract_twice_by_bidder() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let bidder2 = Addr::unchecked("bidder2");
+    let winner = Addr::unchecked("winner");
+    let receiver = Addr::unchecked("receiver");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(10, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder2, coins(15, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &winner, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission: Commission::Rate { rate: Decimal::zero() },
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder2, &coins(15, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(20, ATOM)).unwrap();
+
+    // Nothing can be claimed while bidding is open
+    assert_eq!(BiddingContract::query_claimable(&contract, &app, AUCTION_ID, &bidder).unwrap(), coins(0, ATOM)[0]);
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    assert_eq!(BiddingContract::query_claimable(&contract, &app, AUCTION_ID, &bidder).unwrap(), coins(10, ATOM)[0]);
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(BiddingContract::query_claimable(&contract, &app, AUCTION_ID, &bidder).unwrap(), coins(0, ATOM)[0]);
+
+    // Expecting error, also when paying out to another receiver
+    let err = BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap_err();
+    assert_eq!(err, ContractError::AlreadyClaimed {});
+
+    let err = BiddingContract::retract_to(&contract, &mut app, AUCTION_ID, &bidder, &receiver).unwrap_err();
+    assert_eq!(err, ContractError::AlreadyClaimed {});
+
+    // The funds of the other bidder are untouched
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(10, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), coins(15, ATOM));
+
+    BiddingContract::retract_to(&contract, &mut app, AUCTION_ID, &bidder2, &receiver).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&receiver).unwrap(), coins(15, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn instantiate_with_invalid_time_window() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+    let contract_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+
+    // Expecting error
+    let err = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            start_time: Some(now.plus_seconds(100)),
+            end_time: Some(now.plus_seconds(50)),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap_err();
+
+    assert_eq!(err, ContractError::InvalidTimeWindow {});
+}
+
+#[test]
+fn instantiate_with_invalid_commission() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+    let contract_id = BiddingContract::store_code(&mut app);
+
+    // Expecting error
+    let err = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission: Commission::Rate { rate: Decimal::percent(150) },
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommission {});
+
+    // Expecting error
+    let err = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission: Commission::Tiered { tiers: vec![
+                CommissionTier { min_bid: Uint128::new(100), rate: Decimal::percent(5) },
+                CommissionTier { min_bid: Uint128::new(0), rate: Decimal::percent(10) },
+            ]},
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommission {});
+}
+
+#[test]
+fn tiered_and_flat_commission() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(200, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission: Commission::Tiered { tiers: vec![
+                CommissionTier { min_bid: Uint128::new(0), rate: Decimal::percent(10) },
+                CommissionTier { min_bid: Uint128::new(100), rate: Decimal::percent(5) },
+            ]},
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // The top-up reaches the lower rate of the second tier
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(50, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(60, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_address(&contract, &app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(resp.commission, coins(8, ATOM)[0]);
+    assert_eq!(resp.net, coins(102, ATOM)[0]);
+
+    let flat_id = BiddingContract::create_auction(&contract, &mut app, &owner, InstantiateMsg {
+        commission: Commission::Flat { amount: Uint128::new(3) },
+        ..instantiate_msg(&owner)
+    }).unwrap();
+
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, flat_id, &bidder, &coins(2, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BidBelowCommission { commission: "3".to_string() });
+
+    BiddingContract::bid(&contract, &mut app, flat_id, &bidder, &coins(20, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(11, ATOM));
+}
+
+#[test]
+fn commission_split_between_fee_recipients() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let referrer = Addr::unchecked("referrer");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(200, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+
+    // Expecting error
+    let err = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            fee_recipients: Some(vec![
+                FeeRecipient { address: treasury.to_string(), share: Decimal::percent(50) },
+                FeeRecipient { address: owner.to_string(), share: Decimal::percent(40) },
+            ]),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap_err();
+    assert_eq!(err, ContractError::InvalidFeeRecipients {});
+
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            fee_recipients: Some(vec![
+                FeeRecipient { address: treasury.to_string(), share: Decimal::percent(50) },
+                FeeRecipient { address: owner.to_string(), share: Decimal::percent(30) },
+                FeeRecipient { address: referrer.to_string(), share: Decimal::percent(20) },
+            ]),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Commission of 7 splits into 3, 2 and 1, the remainder goes to the treasury
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(70, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&treasury).unwrap(), coins(4, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(2, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&referrer).unwrap(), coins(1, ATOM));
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(100, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_fees(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.fees, vec![
+        FeeResp { address: owner.to_string(), amount: coins(5, ATOM)[0].clone() },
+        FeeResp { address: referrer.to_string(), amount: coins(3, ATOM)[0].clone() },
+        FeeResp { address: treasury.to_string(), amount: coins(9, ATOM)[0].clone() },
+    ]);
+}
+
+#[test]
+fn escrowed_commission_refunded_to_losers() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let winner = Addr::unchecked("winner");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(50, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &winner, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission_policy: Some(CommissionPolicy::Escrowed {}),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.commission_policy, CommissionPolicy::Escrowed {});
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(50, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(100, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), vec![]);
+
+    // Only the commission of the winner is charged
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(100, ATOM));
+
+    assert_eq!(BiddingContract::query_claimable(&contract, &app, AUCTION_ID, &bidder).unwrap(), coins(50, ATOM)[0]);
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(50, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn bid_outside_time_window() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            start_time: Some(now.plus_seconds(100)),
+            end_time: Some(now.plus_seconds(200)),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BiddingNotStarted { start_time: now.plus_seconds(100).to_string() });
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed {});
+}
+
+#[test]
+fn close_by_anyone_after_end_time() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    let mut app = App::default();
+    let contract_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            end_time: Some(now.plus_seconds(100)),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Expecting error
+    let err = BiddingContract::close(&contract, &mut app, AUCTION_ID, &bidder).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: owner.to_string() });
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert!(!resp.is_open);
+}
+
+#[test]
+fn late_bid_extends_end_time() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder_0 = Addr::unchecked("bidder_0");
+    let bidder_1 = Addr::unchecked("bidder_1");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder_0, coins(20, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder_1, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            end_time: Some(now.plus_seconds(100)),
+            soft_close: Some(SoftClose {
+                window: 10,
+                extension: 30,
+            }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Bid outside the window does not extend
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(10, ATOM)).unwrap();
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.end_time, Some(now.plus_seconds(100)));
+    assert_eq!(resp.extensions, 0);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(95));
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_1, &coins(20, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.end_time, Some(now.plus_seconds(130)));
+    assert_eq!(resp.extensions, 1);
+
+    // Bidding is still possible after the original end time
+    app.update_block(|block| block.time = block.time.plus_seconds(10));
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(5, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BidToLow { higest_bid: "20".to_string() });
+}
+
+#[test]
+fn close_below_reserve_price() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            bid_asset: Coin {
+                denom: ATOM.to_string(),
+                amount: Uint128::new(50),
+            },
+            hidden_reserve: true,
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(20, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.bid_asset.amount, Uint128::new(0));
+    assert!(!resp.reserve_met);
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+
+    let resp = BiddingContract::query_winner(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, WinnerResp {
+        address: "".to_string(),
+        bid: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(0),
+        },
+        clearing_price: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(0),
+        },
+        unsold: true,
+    });
+
+    // Only the commission is paid to the owner, the highest bidder can retract
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(2, ATOM));
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(18, ATOM));
+}
+
+#[test]
+fn bid_below_min_increment() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder_0 = Addr::unchecked("bidder_0");
+    let bidder_1 = Addr::unchecked("bidder_1");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder_0, coins(100, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder_1, coins(110, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission: Commission::Rate { rate: Decimal::zero() },
+            min_increment: Some(BidIncrement::Percentage { percent: Uint128::new(10) }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(100, ATOM)).unwrap();
+    assert_eq!(BiddingContract::query_min_next_bid(&contract, &app, AUCTION_ID).unwrap(), coins(110, ATOM)[0]);
+
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_1, &coins(100, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BidIncrementToLow { min_bid: "110".to_string() });
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_1, &coins(110, ATOM)).unwrap();
+    assert_eq!(BiddingContract::query_min_next_bid(&contract, &app, AUCTION_ID).unwrap(), coins(121, ATOM)[0]);
+}
+
+#[test]
+fn instantiate_with_invalid_increment() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder_0 = Addr::unchecked("bidder_0");
+    let bidder_1 = Addr::unchecked("bidder_1");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder_0, coins(10, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder_1, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+
+    // Expecting error
+    let err = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            min_increment: Some(BidIncrement::Percentage { percent: Uint128::MAX }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap_err();
+    assert_eq!(err, ContractError::InvalidIncrement {});
+
+    // An increment overflowing the next bid fails the bid instead of panicking
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            min_increment: Some(BidIncrement::Absolute { amount: Uint128::MAX }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(10, ATOM)).unwrap();
+    BiddingContract::query_min_next_bid(&contract, &app, AUCTION_ID).unwrap_err();
+
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_1, &coins(20, ATOM)).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+}
+
+#[test]
+fn bid_at_buy_now_price() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder_0 = Addr::unchecked("bidder_0");
+    let bidder_1 = Addr::unchecked("bidder_1");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder_0, coins(50, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder_1, coins(60, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            buy_now: Some(Uint128::new(50)),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_0, &coins(50, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert!(!resp.is_open);
+
+    // Commission and escrowed bid are both paid to the owner
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(50, ATOM));
+
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder_1, &coins(60, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed {});
+}
+
+#[test]
+fn bid_above_buy_now_price_is_refunded() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let buyer = Addr::unchecked("buyer");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(100, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &buyer, coins(500, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            buy_now: Some(Uint128::new(100)),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(40, ATOM)).unwrap();
+
+    // Only the buy-now price is kept, the rest goes straight back
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &buyer, &coins(500, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&buyer).unwrap(), coins(400, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(104, ATOM));
+
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.clearing_price, coin(100, ATOM));
+
+    let resp = BiddingContract::query_claimable(&contract, &app, AUCTION_ID, &buyer).unwrap();
+    assert_eq!(resp, coin(0, ATOM));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(96, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn sealed_bid_commit_and_reveal() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let winner = Addr::unchecked("winner");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(50, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &winner, coins(50, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            end_time: Some(now.plus_seconds(100)),
+            mode: Some(AuctionMode::Sealed {
+                reveal_period: 100,
+                forfeit_unrevealed: false,
+            }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMode {});
+
+    let commitment = sealed_bid_commitment(bidder.as_str(), Uint128::new(20), "bidder salt");
+    BiddingContract::commit(&contract, &mut app, AUCTION_ID, &bidder, commitment, &coins(50, ATOM)).unwrap();
+    let commitment = sealed_bid_commitment(winner.as_str(), Uint128::new(30), "winner salt");
+    BiddingContract::commit(&contract, &mut app, AUCTION_ID, &winner, commitment, &coins(50, ATOM)).unwrap();
+
+    // Committed bids are not visible
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, "");
+
+    // Expecting error
+    let err = BiddingContract::reveal(&contract, &mut app, AUCTION_ID, &winner, 30, "winner salt").unwrap_err();
+    assert_eq!(err, ContractError::RevealNotOpen {});
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    // Expecting error
+    let err = BiddingContract::reveal(&contract, &mut app, AUCTION_ID, &winner, 40, "winner salt").unwrap_err();
+    assert_eq!(err, ContractError::InvalidReveal {});
+
+    BiddingContract::reveal(&contract, &mut app, AUCTION_ID, &winner, 30, "winner salt").unwrap();
+    BiddingContract::reveal(&contract, &mut app, AUCTION_ID, &bidder, 20, "bidder salt").unwrap();
+
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, BidResp {
+        address: winner.to_string(),
+        bid: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(30),
+        },
+        commission: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(3),
+        },
+        net: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(27),
+        },
+    });
+
+    // Deposits above the revealed bids are refunded
+    assert_eq!(app.wrap().query_all_balances(&winner).unwrap(), coins(20, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(30, ATOM));
+
+    // Expecting error
+    let err = BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap_err();
+    assert_eq!(err, ContractError::BiddingOpen {});
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(32, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(48, ATOM));
+}
+
+#[test]
+fn sealed_bid_unrevealed_deposit_forfeited() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(50, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            end_time: Some(now.plus_seconds(100)),
+            mode: Some(AuctionMode::Sealed {
+                reveal_period: 100,
+                forfeit_unrevealed: true,
+            }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    let commitment = sealed_bid_commitment(bidder.as_str(), Uint128::new(20), "salt");
+    BiddingContract::commit(&contract, &mut app, AUCTION_ID, &bidder, commitment.clone(), &coins(50, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_commitment(&contract, &app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(resp.commitment, Some(commitment));
+    assert_eq!(resp.deposit, coins(50, ATOM)[0]);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(200));
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+
+    // Expecting error
+    let err = BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap_err();
+    assert_eq!(err, ContractError::DepositForfeited {});
+
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(50, ATOM));
+}
+
+#[test]
+fn second_price_settlement() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let winner = Addr::unchecked("winner");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(10, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &winner, coins(30, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission: Commission::Rate { rate: Decimal::zero() },
+            second_price: true,
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(30, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_winner(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, WinnerResp {
+        address: winner.to_string(),
+        bid: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(30),
+        },
+        clearing_price: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(10),
+        },
+        unsold: false,
+    });
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(10, ATOM));
+
+    // Winner retracts the surplus over the clearing price
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &winner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&winner).unwrap(), coins(20, ATOM));
+
+    // Expecting error
+    let err = BiddingContract::retract(&contract, &mut app, AUCTION_ID, &winner).unwrap_err();
+    assert_eq!(err, ContractError::AlreadyClaimed {});
+}
+
+#[test]
+fn second_price_commission_on_clearing_price() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let fees = Addr::unchecked("fees");
+    let bidder = Addr::unchecked("bidder");
+    let winner = Addr::unchecked("winner");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(10, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &winner, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            fee_recipients: Some(vec![
+                FeeRecipient { address: fees.to_string(), share: Decimal::one() },
+            ]),
+            second_price: true,
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Commission of the leader is held until the clearing price is known
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&fees).unwrap(), vec![]);
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(100, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&fees).unwrap(), coins(1, ATOM));
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&fees).unwrap(), coins(2, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(9, ATOM));
+
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.clearing_price, coin(10, ATOM));
+    assert_eq!(resp.commission, coin(2, ATOM));
+    assert_eq!(resp.proceeds, coin(9, ATOM));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &winner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&winner).unwrap(), coins(90, ATOM));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(9, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn second_price_with_hidden_reserve() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let winner = Addr::unchecked("winner");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(50, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &winner, coins(500, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let msg = InstantiateMsg {
+        bid_asset: Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(300),
+        },
+        commission: Commission::Rate { rate: Decimal::zero() },
+        hidden_reserve: true,
+        second_price: true,
+        ..instantiate_msg(&owner)
+    };
+
+    // Expecting error
+    let err = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            buy_now: Some(Uint128::new(400)),
+            ..msg.clone()
+        },
+    ).unwrap_err();
+    assert_eq!(err, ContractError::InvalidBuyNow {});
+
+    let contract = BiddingContract::instantiate_with_msg(&mut app, contract_id, &sender, "Bidding contract", &owner, &msg).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(50, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(500, ATOM)).unwrap();
+
+    // The hidden reserve price is left out until the auction closes
+    let resp = BiddingContract::query_winner(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.clearing_price, coins(50, ATOM)[0]);
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+
+    let resp = BiddingContract::query_winner(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.clearing_price, coins(300, ATOM)[0]);
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(300, ATOM));
+}
+
+#[test]
+fn dutch_auction_bid_at_current_price() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(70, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            mode: Some(AuctionMode::Dutch {
+                start_price: Uint128::new(100),
+                floor_price: Uint128::new(20),
+                decline_period: 80,
+                step: None,
+            }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    assert_eq!(BiddingContract::query_current_price(&contract, &app, AUCTION_ID).unwrap(), coins(100, ATOM)[0]);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(40));
+    assert_eq!(BiddingContract::query_current_price(&contract, &app, AUCTION_ID).unwrap(), coins(60, ATOM)[0]);
+
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(50, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BidBelowPrice { price: "60".to_string() });
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(70, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert!(!resp.is_open);
+
+    // Paid above the current price is refunded
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(10, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(60, ATOM));
+}
+
+#[test]
+fn concurrent_auctions_are_isolated() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let other_owner = Addr::unchecked("other_owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(200, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
+    ).unwrap();
+
+    let second_id = BiddingContract::create_auction(&contract, &mut app, &other_owner, InstantiateMsg {
+        commodity: "Other item".to_string(),
+        owner: None,
+        ..instantiate_msg(&owner)
+    }).unwrap();
+    assert_eq!(second_id, 2);
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(50, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, second_id, &bidder, &coins(100, ATOM)).unwrap();
+
+    assert_eq!(BiddingContract::query_address(&contract, &app, AUCTION_ID, &bidder).unwrap().net, coins(45, ATOM)[0]);
+    assert_eq!(BiddingContract::query_address(&contract, &app, second_id, &bidder).unwrap().net, coins(90, ATOM)[0]);
+
+    // Only the owner of the second auction may close it
+    let err = BiddingContract::close(&contract, &mut app, second_id, &owner).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: other_owner.to_string() });
+
+    BiddingContract::close(&contract, &mut app, second_id, &other_owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&other_owner).unwrap(), coins(100, ATOM));
+
+    let resp = BiddingContract::query_auctions(&contract, &app, None, None).unwrap();
+    assert_eq!(resp.auctions.len(), 2);
+    assert!(resp.auctions[0].is_open);
+    assert_eq!(resp.auctions[1].commodity, "Other item");
+    assert_eq!(resp.auctions[1].owner, Some(other_owner.to_string()));
+    assert!(!resp.auctions[1].is_open);
+
+    let resp = BiddingContract::query_auctions(&contract, &app, Some(AUCTION_ID), None).unwrap();
+    assert_eq!(resp.auctions.len(), 1);
+    assert_eq!(resp.auctions[0].auction_id, second_id);
+}
+
+#[test]
+fn factory_registers_created_auctions() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let other_owner = Addr::unchecked("other_owner");
+    let new_owner = Addr::unchecked("new_owner");
+
+    let mut app = App::default();
+    let bidding_id = BiddingContract::store_code(&mut app);
+    let factory_id = FactoryContract::store_code(&mut app);
+    let factory = FactoryContract::instantiate(&mut app, factory_id, &sender, "Auction factory", &sender, bidding_id).unwrap();
+
+    let first = FactoryContract::create_auction(&factory, &mut app, &sender, instantiate_msg(&owner)).unwrap();
+    let second = FactoryContract::create_auction(&factory, &mut app, &other_owner, InstantiateMsg {
+        owner: None,
+        ..instantiate_msg(&owner)
+    }).unwrap();
+
+    // The factory is the admin, not the owner holding the bids
+    let info = app.wrap().query_wasm_contract_info(&first).unwrap();
+    assert_eq!(info.admin, Some(factory.addr().to_string()));
+
+    // Only the admin of the factory migrates its contracts
+    let new_bidding_id = BiddingContract::store_code(&mut app);
+    let err = FactoryContract::migrate_auction(&factory, &mut app, &owner, &first, new_bidding_id).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: sender.to_string() });
+
+    let err = FactoryContract::migrate_auction(&factory, &mut app, &sender, factory.addr(), new_bidding_id).unwrap_err();
+    assert_eq!(err, ContractError::UnregisteredContract {});
+
+    FactoryContract::migrate_auction(&factory, &mut app, &sender, &first, new_bidding_id).unwrap();
+    let info = app.wrap().query_wasm_contract_info(&first).unwrap();
+    assert_eq!(info.code_id, new_bidding_id);
+
+    // Created auctions are owned by the given owner or the sender, not the factory
+    let contract = BiddingContract::from_addr(second.clone());
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.owner, Some(other_owner.to_string()));
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &other_owner).unwrap();
+
+    // Later auctions of a registered contract are registered as well
+    let contract = BiddingContract::from_addr(first.clone());
+    let auction_id = BiddingContract::create_auction(&contract, &mut app, &owner, instantiate_msg(&owner)).unwrap();
+
+    let resp = FactoryContract::query_auctions(&factory, &app, None, None).unwrap();
+    assert_eq!(resp.auctions.len(), 3);
+
+    let resp = FactoryContract::query_auctions(&factory, &app, None, Some(1)).unwrap();
+    let last = &resp.auctions[0];
+    let resp = FactoryContract::query_auctions(&factory, &app, Some((&Addr::unchecked(&last.address), last.auction_id)), None).unwrap();
+    assert_eq!(resp.auctions.len(), 2);
+
+    // Ownership transfers are followed by the owner index
+    BiddingContract::propose_owner(&contract, &mut app, AUCTION_ID, &owner, &new_owner, None).unwrap();
+    BiddingContract::accept_ownership(&contract, &mut app, AUCTION_ID, &new_owner).unwrap();
+
+    let resp = FactoryContract::query_auctions_by_owner(&factory, &app, &owner).unwrap();
+    assert_eq!(resp.auctions, vec![AuctionInfo {
+        address: first.to_string(),
+        auction_id,
+        owner: Some(owner.to_string()),
+        is_open: true,
+    }]);
+
+    let resp = FactoryContract::query_auctions_by_owner(&factory, &app, &new_owner).unwrap();
+    assert_eq!(resp.auctions, vec![AuctionInfo {
+        address: first.to_string(),
+        auction_id: AUCTION_ID,
+        owner: Some(new_owner.to_string()),
+        is_open: true,
+    }]);
+
+    let resp = FactoryContract::query_auctions_by_status(&factory, &app, false).unwrap();
+    assert_eq!(resp.auctions, vec![AuctionInfo {
+        address: second.to_string(),
+        auction_id: AUCTION_ID,
+        owner: Some(other_owner.to_string()),
+        is_open: false,
+    }]);
+
+    // Only registered contracts update the registry
+    let err = app.execute_contract(
+        sender.clone(),
+        factory.addr().clone(),
+        &crate::factory::msg::ExecMsg::UpdateAuction { auction_id: AUCTION_ID, owner: None, is_open: false },
+        &[],
+    ).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::UnregisteredContract {});
+
+    // A failing registry does not block the auction
+    let contract = BiddingContract::instantiate_with_msg(&mut app, bidding_id, &sender, "Bidding contract", &owner, &InstantiateMsg {
+        registry: Some("registry".to_string()),
+        ..instantiate_msg(&owner)
+    }).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+}
+
+fn nft_owner(app: &App, nft_contract: &Addr, token_id: &str) -> String {
+    let resp: OwnerOfResponse = app.wrap()
+        .query_wasm_smart(nft_contract, &Cw721QueryMsg::OwnerOf { token_id: token_id.to_string(), include_expired: None })
+        .unwrap();
+
+    resp.owner
+}
+
+#[test]
+fn nft_auction_delivers_to_winner() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let nft_id = app.store_code(Box::new(ContractWrapper::new(
+        cw721_base::entry::execute,
+        cw721_base::entry::instantiate,
+        cw721_base::entry::query,
+    )));
+    let nft_contract = app.instantiate_contract(
+        nft_id,
+        sender.clone(),
+        &cw721_base::InstantiateMsg {
+            name: "Items".to_string(),
+            symbol: "ITEM".to_string(),
+            minter: sender.to_string(),
+        },
+        &[],
+        "Items",
+        None,
+    ).unwrap();
+
+    for token_id in ["sold", "unsold"] {
+        app.execute_contract(
+            sender.clone(),
+            nft_contract.clone(),
+            &cw721_base::ExecuteMsg::<Option<Empty>, Empty>::Mint {
+                token_id: token_id.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: None,
+            },
+            &[],
+        ).unwrap();
+    }
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        None,
+        "Item",
+        Coin {
+            denom: ATOM.to_string(),
+            amount: Uint128::new(0),
+        },
+        Commission::Rate { rate: Decimal::percent(10) },
+    ).unwrap();
+
+    // The owner given in the message is replaced by the sender of the NFT
+    let sold_id = BiddingContract::create_nft_auction(&contract, &mut app, &owner, &nft_contract, "sold", &instantiate_msg(&sender)).unwrap();
+    let unsold_id = BiddingContract::create_nft_auction(&contract, &mut app, &owner, &nft_contract, "unsold", &instantiate_msg(&sender)).unwrap();
+    assert_eq!(nft_owner(&app, &nft_contract, "sold"), contract.addr().to_string());
+
+    let resp = BiddingContract::query_auction(&contract, &app, sold_id).unwrap();
+    assert_eq!(resp.owner, Some(owner.to_string()));
+    assert_eq!(resp.nft, Some(Nft {
+        contract: nft_contract.clone(),
+        token_id: "sold".to_string(),
+    }));
+
+    BiddingContract::bid(&contract, &mut app, sold_id, &bidder, &coins(100, ATOM)).unwrap();
+    BiddingContract::close(&contract, &mut app, sold_id, &owner).unwrap();
+    BiddingContract::close(&contract, &mut app, unsold_id, &owner).unwrap();
+
+    assert_eq!(nft_owner(&app, &nft_contract, "sold"), bidder.to_string());
+    assert_eq!(nft_owner(&app, &nft_contract, "unsold"), owner.to_string());
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(100, ATOM));
+}
+
+#[test]
+fn receive_nft_requires_the_token() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+    let nft_id = app.store_code(Box::new(ContractWrapper::new(
+        cw721_base::entry::execute,
+        cw721_base::entry::instantiate,
+        cw721_base::entry::query,
+    )));
+    let nft_contract = app.instantiate_contract(
+        nft_id,
+        sender.clone(),
+        &cw721_base::InstantiateMsg {
+            name: "Items".to_string(),
+            symbol: "ITEM".to_string(),
+            minter: sender.to_string(),
+        },
+        &[],
+        "Items",
+        None,
+    ).unwrap();
+
+    app.execute_contract(
+        sender.clone(),
+        nft_contract.clone(),
+        &cw721_base::ExecuteMsg::<Option<Empty>, Empty>::Mint {
+            token_id: "kept".to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        },
+        &[],
+    ).unwrap();
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        None,
+        "Item",
+        coins(0, ATOM)[0].clone(),
+        Commission::Rate { rate: Decimal::percent(10) },
+    ).unwrap();
+
+    let receive = |token_id: &str| ExecMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: owner.to_string(),
+        token_id: token_id.to_string(),
+        msg: to_json_binary(&instantiate_msg(&owner)).unwrap(),
+    });
+
+    // Expecting error, the sender is not an NFT contract
+    let err = app.execute_contract(owner.clone(), contract.addr().clone(), &receive("kept"), &[]).unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Std(_)));
+
+    // Expecting error, the NFT was never transferred to the contract
+    let err = app.execute_contract(nft_contract.clone(), contract.addr().clone(), &receive("kept"), &[]).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NftNotHeld {});
+}
+
+fn cw20_balance(app: &App, token: &Addr, address: &Addr) -> u128 {
+    let resp: BalanceResponse = app.wrap()
+        .query_wasm_smart(token, &Cw20QueryMsg::Balance { address: address.to_string() })
+        .unwrap();
+
+    resp.balance.u128()
+}
+
+#[test]
+fn bid_with_cw20_token() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let bidder2 = Addr::unchecked("bidder2");
+
+    let mut app = App::default();
+
+    let token_id = app.store_code(Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    )));
+    let token = app.instantiate_contract(
+        token_id,
+        sender.clone(),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Community token".to_string(),
+            symbol: "COMM".to_string(),
+            decimals: 6,
+            initial_balances: vec![
+                Cw20Coin { address: bidder.to_string(), amount: Uint128::new(100) },
+                Cw20Coin { address: bidder2.to_string(), amount: Uint128::new(100) },
+            ],
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        "Community token",
+        None,
+    ).unwrap();
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            bid_asset: Coin {
+                denom: token.to_string(),
+                amount: Uint128::new(0),
+            },
+            cw20: true,
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Expecting error
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &[]).unwrap_err();
+    assert_eq!(err, ContractError::InvalidDenomination { denom: token.to_string() });
+
+    BiddingContract::bid_cw20(&contract, &mut app, AUCTION_ID, &bidder, &token, 50).unwrap();
+    BiddingContract::bid_cw20(&contract, &mut app, AUCTION_ID, &bidder2, &token, 100).unwrap();
+
+    assert_eq!(BiddingContract::query_address(&contract, &app, AUCTION_ID, &bidder).unwrap().net, coins(45, token.as_str())[0]);
+    assert_eq!(cw20_balance(&app, &token, &owner), 15);
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    assert_eq!(cw20_balance(&app, &token, &owner), 105);
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(cw20_balance(&app, &token, &bidder), 95);
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 0);
+}
+
+#[test]
+fn cancel_refunds_cw20_commission() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    let mut app = App::default();
+
+    let token_id = app.store_code(Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    )));
+    let token = app.instantiate_contract(
+        token_id,
+        sender.clone(),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Community token".to_string(),
+            symbol: "COMM".to_string(),
+            decimals: 6,
+            initial_balances: vec![
+                Cw20Coin { address: bidder.to_string(), amount: Uint128::new(100) },
+            ],
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        "Community token",
+        None,
+    ).unwrap();
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            bid_asset: Coin {
+                denom: token.to_string(),
+                amount: Uint128::new(0),
+            },
+            cw20: true,
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid_cw20(&contract, &mut app, AUCTION_ID, &bidder, &token, 50).unwrap();
+    assert_eq!(cw20_balance(&app, &token, &owner), 5);
+
+    // Expecting error
+    let err = BiddingContract::cancel(&contract, &mut app, AUCTION_ID, &owner, None, true, &[]).unwrap_err();
+    assert_eq!(err, ContractError::CommissionRefund { amount: "5".to_string() });
+
+    let err = BiddingContract::cancel_cw20(&contract, &mut app, AUCTION_ID, &owner, &token, 4).unwrap_err();
+    assert_eq!(err, ContractError::CommissionRefund { amount: "5".to_string() });
+
+    BiddingContract::cancel_cw20(&contract, &mut app, AUCTION_ID, &owner, &token, 5).unwrap();
+    assert_eq!(cw20_balance(&app, &token, &owner), 0);
+
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.commission, coin(0, token.as_str()));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(cw20_balance(&app, &token, &bidder), 100);
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 0);
+}
+
+#[test]
+fn proxy_bid_raises_by_increment() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let proxy = Addr::unchecked("proxy");
+    let bidder = Addr::unchecked("bidder");
+    let challenger = Addr::unchecked("challenger");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &proxy, coins(100, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(20, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &challenger, coins(200, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            bid_asset: coin(10, ATOM),
+            fee_recipients: Some(vec![FeeRecipient {
+                address: "fees".to_string(),
+                share: Decimal::one(),
+            }]),
+            min_increment: Some(BidIncrement::Absolute { amount: Uint128::new(5) }),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // The proxy opens at the reserve price, the maximum stays hidden
+    BiddingContract::proxy_bid(&contract, &mut app, AUCTION_ID, &proxy, &coins(100, ATOM)).unwrap();
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, BidResp {
+        address: proxy.to_string(),
+        bid: coin(10, ATOM),
+        commission: coin(1, ATOM),
+        net: coin(9, ATOM),
+    });
+    assert_eq!(BiddingContract::query_address(&contract, &app, AUCTION_ID, &proxy).unwrap(), resp);
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(20, ATOM)).unwrap();
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, proxy.as_str());
+    assert_eq!(resp.bid, coin(25, ATOM));
+
+    // Competing proxies, the higest maximum leads just above the other one
+    BiddingContract::proxy_bid(&contract, &mut app, AUCTION_ID, &challenger, &coins(60, ATOM)).unwrap();
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, proxy.as_str());
+    assert_eq!(resp.bid, coin(65, ATOM));
+
+    let err = BiddingContract::proxy_bid(&contract, &mut app, AUCTION_ID, &challenger, &coins(8, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BidIncrementToLow { min_bid: "70".to_string() });
+
+    // On a tie the earlier bid wins
+    BiddingContract::proxy_bid(&contract, &mut app, AUCTION_ID, &challenger, &coins(40, ATOM)).unwrap();
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, proxy.as_str());
+    assert_eq!(resp.bid, coin(100, ATOM));
+
+    // Outbidding the maximum takes the lead with the next increment
+    BiddingContract::proxy_bid(&contract, &mut app, AUCTION_ID, &challenger, &coins(50, ATOM)).unwrap();
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, challenger.as_str());
+    assert_eq!(resp.bid, coin(105, ATOM));
+
+    // The winner pays the current price and retracts the rest of the deposit
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    let resp = BiddingContract::query_winner(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, challenger.as_str());
+    assert_eq!(resp.clearing_price, coin(105, ATOM));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &challenger).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&challenger).unwrap(), coins(95, ATOM));
+
+    // The outbid proxy paid commission on its maximum, the winner only on the price
+    assert_eq!(app.wrap().query_all_balances("fees").unwrap(), coins(22, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(95, ATOM));
+}
+
+#[test]
+fn proxy_winner_pays_commission_on_price() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let proxy = Addr::unchecked("proxy");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &proxy, coins(1000, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            bid_asset: coin(50, ATOM),
+            fee_recipients: Some(vec![FeeRecipient {
+                address: "fees".to_string(),
+                share: Decimal::one(),
+            }]),
+            hidden_reserve: true,
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // The proxy does not reveal the hidden reserve price by jumping to it
+    BiddingContract::proxy_bid(&contract, &mut app, AUCTION_ID, &proxy, &coins(1000, ATOM)).unwrap();
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, BidResp {
+        address: proxy.to_string(),
+        bid: coin(1, ATOM),
+        commission: coin(0, ATOM),
+        net: coin(1, ATOM),
+    });
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert!(resp.reserve_met);
+
+    // On close the proxy bids up to the reserve price and pays commission on it
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    let resp = BiddingContract::query_winner(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.clearing_price, coin(50, ATOM));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &proxy).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&proxy).unwrap(), coins(950, ATOM));
+    assert_eq!(app.wrap().query_all_balances("fees").unwrap(), coins(5, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(45, ATOM));
+}
+
+#[test]
+fn all_bids_ordered_by_amount() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidders = [Addr::unchecked("bidder1"), Addr::unchecked("bidder2"), Addr::unchecked("bidder3")];
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in &bidders {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(100, ATOM))
+                .unwrap();
+        }
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidders[0], &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidders[2], &coins(20, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidders[1], &coins(30, ATOM)).unwrap();
+
+    let addresses = |bids: Vec<BidResp>| bids.into_iter().map(|bid| bid.address).collect::<Vec<_>>();
+
+    // Highest first by default, paginated by address
+    let resp = BiddingContract::query_all_bids(&contract, &app, AUCTION_ID, None, Some(2), None).unwrap();
+    assert_eq!(addresses(resp.bids), vec!["bidder2", "bidder3"]);
+    assert_eq!(resp.count, 3);
+    assert_eq!(resp.escrowed, coin(54, ATOM));
+
+    let resp = BiddingContract::query_all_bids(&contract, &app, AUCTION_ID, Some(&bidders[2]), Some(2), None).unwrap();
+    assert_eq!(addresses(resp.bids), vec!["bidder1"]);
+
+    let resp = BiddingContract::query_all_bids(&contract, &app, AUCTION_ID, None, None, Some(SortOrder::Ascending)).unwrap();
+    assert_eq!(addresses(resp.bids), vec!["bidder1", "bidder3", "bidder2"]);
+
+    // Topping up moves the bid in the index
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidders[0], &coins(30, ATOM)).unwrap();
+    let resp = BiddingContract::query_all_bids(&contract, &app, AUCTION_ID, None, None, None).unwrap();
+    assert_eq!(addresses(resp.bids), vec!["bidder1", "bidder2", "bidder3"]);
+    assert_eq!(resp.count, 3);
+    assert_eq!(resp.escrowed, coin(81, ATOM));
+
+    // Paid and retracted funds are no longer escrowed
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidders[2]).unwrap();
+    let resp = BiddingContract::query_all_bids(&contract, &app, AUCTION_ID, None, None, None).unwrap();
+    assert_eq!(resp.count, 3);
+    assert_eq!(resp.escrowed, coin(27, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), coins(27, ATOM));
+}
+
+#[test]
+fn bid_history_records_top_ups() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder1 = Addr::unchecked("bidder1");
+    let bidder2 = Addr::unchecked("bidder2");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder1, coins(100, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder2, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder1, &coins(10, ATOM)).unwrap();
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(5);
+    });
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder2, &coins(20, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder1, &coins(20, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_bid_history(&contract, &app, AUCTION_ID, &bidder1, None, None).unwrap();
+    assert_eq!(
+        resp.events.iter().map(|event| (event.event_id, event.bid.clone(), event.commission, event.total.clone())).collect::<Vec<_>>(),
+        vec![
+            (1, coin(10, ATOM), Uint128::new(1), coin(10, ATOM)),
+            (3, coin(20, ATOM), Uint128::new(2), coin(30, ATOM)),
+        ]
+    );
+    assert_eq!(resp.events[1].height, resp.events[0].height + 1);
+    assert_eq!(resp.events[1].time, resp.events[0].time.plus_seconds(5));
+
+    let resp = BiddingContract::query_bid_history(&contract, &app, AUCTION_ID, &bidder1, Some(1), None).unwrap();
+    assert_eq!(resp.events.len(), 1);
+    assert_eq!(resp.events[0].event_id, 3);
+
+    let resp = BiddingContract::query_history(&contract, &app, AUCTION_ID, None, Some(2)).unwrap();
+    assert_eq!(
+        resp.events.iter().map(|event| event.address.as_str()).collect::<Vec<_>>(),
+        vec!["bidder1", "bidder2"]
+    );
+
+    let resp = BiddingContract::query_history(&contract, &app, AUCTION_ID, Some(2), None).unwrap();
+    assert_eq!(resp.events.len(), 1);
+    assert_eq!(resp.events[0].address, "bidder1");
+    assert_eq!(resp.events[0].total, coin(30, ATOM));
+}
+
+#[test]
+fn settlement_report() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+    let winner = Addr::unchecked("winner");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(100, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &winner, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &winner, &coins(20, ATOM)).unwrap();
+
+    BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap_err();
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+
+    let block = app.block_info();
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, SettlementResp {
+        winner: Some(winner.to_string()),
+        clearing_price: coin(20, ATOM),
+        commission: coin(3, ATOM),
+        proceeds: coin(18, ATOM),
+        height: block.height,
+        time: block.time,
+        outcome: Outcome::Sold {},
+    });
+
+    // The winner is still reported once closed
+    let resp = BiddingContract::query_winner(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, winner.as_str());
+    assert_eq!(resp.clearing_price, coin(20, ATOM));
+    assert!(!resp.unsold);
+
+    // Below the reserve price nothing is paid to the owner
+    let auction_id = BiddingContract::create_auction(&contract, &mut app, &owner, InstantiateMsg {
+        bid_asset: coin(50, ATOM),
+        ..instantiate_msg(&owner)
+    }).unwrap();
+    BiddingContract::bid(&contract, &mut app, auction_id, &bidder, &coins(30, ATOM)).unwrap();
+    BiddingContract::close(&contract, &mut app, auction_id, &owner).unwrap();
+
+    let resp = BiddingContract::query_settlement(&contract, &app, auction_id).unwrap();
+    assert_eq!(resp.winner, None);
+    assert_eq!(resp.commission, coin(3, ATOM));
+    assert_eq!(resp.proceeds, coin(0, ATOM));
+    assert_eq!(resp.outcome, Outcome::Unsold {});
+
+    let resp = BiddingContract::query_winner(&contract, &app, auction_id).unwrap();
+    assert_eq!(resp.address, "");
+    assert!(resp.unsold);
+}
+
+/// Contract in the single auction layout of 0.1.0 with two bids.
+fn instantiate_v0_1_0(deps: DepsMut, _env: Env, info: MessageInfo, msg: ContractVersion) -> StdResult<Response> {
+    use crate::state::{legacy, Bid};
+
+    cw2::set_contract_version(deps.storage, msg.contract, msg.version)?;
+
+    legacy::OWNER.save(deps.storage, &info.sender)?;
+    legacy::COMMODITY.save(deps.storage, &"Item".to_string())?;
+    legacy::BID_ASSET.save(deps.storage, &coin(1, ATOM))?;
+    legacy::COMMISSION.save(deps.storage, &Uint128::new(10))?;
+    legacy::IS_OPEN.save(deps.storage, &true)?;
+    legacy::BIDS.save(deps.storage, Addr::unchecked("bidder1"), &coin(9, ATOM))?;
+    legacy::BIDS.save(deps.storage, Addr::unchecked("bidder2"), &coin(18, ATOM))?;
+    legacy::HIGEST_BID.save(deps.storage, &Bid {
+        address: Addr::unchecked("bidder2"),
+        bid: coin(20, ATOM),
+    })?;
+
+    Ok(Response::new())
+}
+
+fn instantiate_legacy(app: &mut App, owner: &Addr, contract: &str, version: &str) -> BiddingContract {
+    let code_id = app.store_code(Box::new(ContractWrapper::new(crate::execute, instantiate_v0_1_0, crate::query)));
+
+    let addr = app.instantiate_contract(
+        code_id,
+        owner.clone(),
+        &ContractVersion {
+            contract: contract.to_string(),
+            version: version.to_string(),
+        },
+        &coins(27, ATOM),
+        "Legacy bidding contract",
+        Some(owner.to_string()),
+    ).unwrap();
+
+    BiddingContract::from_addr(addr)
+}
+
+/// Contract of 0.1.0 after closing, only the bids of bidders who did not retract are still held.
+fn instantiate_closed_v0_1_0(mut deps: DepsMut, env: Env, info: MessageInfo, msg: ContractVersion) -> StdResult<Response> {
+    instantiate_v0_1_0(deps.branch(), env, info, msg)?;
+    crate::state::legacy::IS_OPEN.save(deps.storage, &false)?;
+
+    Ok(Response::new())
+}
+
+fn instantiate_closed_legacy(app: &mut App, owner: &Addr, held: u128) -> BiddingContract {
+    let code_id = app.store_code(Box::new(ContractWrapper::new(crate::execute, instantiate_closed_v0_1_0, crate::query)));
+    let funds = match held {
+        0 => vec![],
+        held => coins(held, ATOM),
+    };
+
+    let addr = app.instantiate_contract(
+        code_id,
+        owner.clone(),
+        &ContractVersion {
+            contract: "bidding_contract".to_string(),
+            version: "0.1.0".to_string(),
+        },
+        &funds,
+        "Legacy bidding contract",
+        Some(owner.to_string()),
+    ).unwrap();
+
+    BiddingContract::from_addr(addr)
+}
+
+#[test]
+fn migrate_legacy_auction() {
+    let owner = Addr::unchecked("owner");
+    let bidder1 = Addr::unchecked("bidder1");
+    let bidder2 = Addr::unchecked("bidder2");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &owner, coins(27, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder1, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract = instantiate_legacy(&mut app, &owner, "bidding_contract", "0.1.0");
+    let contract_id = BiddingContract::store_code(&mut app);
+    BiddingContract::migrate(&contract, &mut app, &owner, contract_id).unwrap();
+
+    let version = cw2::query_contract_info(&app.wrap(), contract.addr()).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // The legacy auction is auction 1, bids keep the amount after commission
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.owner, Some(owner.to_string()));
+    assert_eq!(resp.bid_asset, coin(0, ATOM));
+    assert_eq!(resp.commission, Commission::Rate { rate: Decimal::percent(10) });
+    assert!(resp.is_open);
+
+    let resp = BiddingContract::query_highest_bid(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.address, bidder2.as_str());
+    assert_eq!(resp.bid, coin(18, ATOM));
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder1, &coins(20, ATOM)).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(29, ATOM));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder2).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder2).unwrap(), coins(18, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn migrate_closed_legacy_auction() {
+    let owner = Addr::unchecked("owner");
+    let bidder1 = Addr::unchecked("bidder1");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &owner, coins(14, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+
+    // The losing bid was retracted before the migration
+    let contract = instantiate_closed_legacy(&mut app, &owner, 0);
+    BiddingContract::migrate(&contract, &mut app, &owner, contract_id).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert!(!resp.is_open);
+
+    // The sale is settled at the highest bid after commission
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.outcome, Outcome::Sold {});
+    assert_eq!(resp.winner, Some("bidder2".to_string()));
+    assert_eq!(resp.clearing_price, coin(18, ATOM));
+    assert_eq!(resp.proceeds, coin(18, ATOM));
+    assert_eq!(resp.height, app.block_info().height);
+
+    let resp = BiddingContract::query_all_bids(&contract, &app, AUCTION_ID, None, None, None).unwrap();
+    assert_eq!(resp.escrowed, coin(0, ATOM));
+
+    let err = BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder1).unwrap_err();
+    assert_eq!(err, ContractError::AlreadyClaimed {});
+
+    // The losing bid is still held
+    let contract = instantiate_closed_legacy(&mut app, &owner, 9);
+    BiddingContract::migrate(&contract, &mut app, &owner, contract_id).unwrap();
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder1).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder1).unwrap(), coins(9, ATOM));
+
+    let err = BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder1).unwrap_err();
+    assert_eq!(err, ContractError::AlreadyClaimed {});
+
+    // Neither can be told apart when only part of the losing bids is held
+    let contract = instantiate_closed_legacy(&mut app, &owner, 5);
+    let err = BiddingContract::migrate(&contract, &mut app, &owner, contract_id).unwrap_err();
+    assert_eq!(err, ContractError::PartialRetraction {});
+}
+
+#[test]
+fn migrate_refuses_foreign_contract_and_downgrade() {
+    let owner = Addr::unchecked("owner");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &owner, coins(54, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+
+    let contract = instantiate_legacy(&mut app, &owner, "other_contract", "0.1.0");
+    let err = BiddingContract::migrate(&contract, &mut app, &owner, contract_id).unwrap_err();
+    assert_eq!(err, ContractError::InvalidContract { contract: "other_contract".to_string() });
+
+    let contract = instantiate_legacy(&mut app, &owner, "bidding_contract", "99.0.0");
+    let err = BiddingContract::migrate(&contract, &mut app, &owner, contract_id).unwrap_err();
+    assert_eq!(err, ContractError::Downgrade { version: "99.0.0".to_string() });
+}
+
+#[test]
+fn ownership_transfer_mid_auction() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let agency = Addr::unchecked("agency");
+    let bidder1 = Addr::unchecked("bidder1");
+    let bidder2 = Addr::unchecked("bidder2");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder1, coins(10, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder2, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
+    ).unwrap();
+
+    let err = BiddingContract::propose_owner(&contract, &mut app, AUCTION_ID, &agency, &agency, None).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: owner.to_string() });
+
+    let expiry = Expiration::AtHeight(app.block_info().height + 10);
+    BiddingContract::propose_owner(&contract, &mut app, AUCTION_ID, &owner, &agency, Some(expiry)).unwrap();
+
+    let resp = BiddingContract::query_ownership(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp, OwnershipResp {
+        owner: Some(owner.to_string()),
+        pending_owner: Some(agency.to_string()),
+        pending_expiry: Some(expiry),
+    });
+
+    let err = BiddingContract::accept_ownership(&contract, &mut app, AUCTION_ID, &bidder1).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: agency.to_string() });
+
+    // Commission goes to the owner at the time of the bid
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder1, &coins(10, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(1, ATOM));
+
+    BiddingContract::accept_ownership(&contract, &mut app, AUCTION_ID, &agency).unwrap();
+    let resp = BiddingContract::query_ownership(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.owner, Some(agency.to_string()));
+    assert_eq!(resp.pending_owner, None);
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder2, &coins(20, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&agency).unwrap(), coins(2, ATOM));
+
+    let err = BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: agency.to_string() });
+
+    let err = BiddingContract::renounce_ownership(&contract, &mut app, AUCTION_ID, &agency).unwrap_err();
+    assert_eq!(err, ContractError::BiddingOpen {});
+
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &agency).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&agency).unwrap(), coins(20, ATOM));
+
+    BiddingContract::renounce_ownership(&contract, &mut app, AUCTION_ID, &agency).unwrap();
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.owner, None);
+}
+
+#[test]
+fn ownership_proposal_expires() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let agency = Addr::unchecked("agency");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
+    ).unwrap();
+
+    let err = BiddingContract::accept_ownership(&contract, &mut app, AUCTION_ID, &agency).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingOwner {});
+
+    let expiry = Expiration::AtHeight(app.block_info().height + 1);
+    BiddingContract::propose_owner(&contract, &mut app, AUCTION_ID, &owner, &agency, Some(expiry)).unwrap();
+    app.update_block(|block| block.height += 1);
+
+    let err = BiddingContract::accept_ownership(&contract, &mut app, AUCTION_ID, &agency).unwrap_err();
+    assert_eq!(err, ContractError::OwnershipProposalExpired {});
+
+    // Bidders can not take over the auction
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(10, ATOM)).unwrap();
+    BiddingContract::propose_owner(&contract, &mut app, AUCTION_ID, &owner, &bidder, None).unwrap();
+
+    let err = BiddingContract::accept_ownership(&contract, &mut app, AUCTION_ID, &bidder).unwrap_err();
+    assert_eq!(err, ContractError::BiddingByOwner {});
+}
+
+#[test]
+fn cancel_refunds_all_bids() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder1 = Addr::unchecked("bidder1");
+    let bidder2 = Addr::unchecked("bidder2");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder1, coins(10, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder2, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(&owner),
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder1, &coins(10, ATOM)).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder2, &coins(20, ATOM)).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(3, ATOM));
+
+    let err = BiddingContract::cancel(&contract, &mut app, AUCTION_ID, &bidder1, None, false, &[]).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: owner.to_string() });
+
+    // The commission already paid has to be sent back
+    let err = BiddingContract::cancel(&contract, &mut app, AUCTION_ID, &owner, None, true, &[]).unwrap_err();
+    assert_eq!(err, ContractError::CommissionRefund { amount: "3".to_string() });
+
+    BiddingContract::cancel(&contract, &mut app, AUCTION_ID, &owner, Some("Withdrawn"), true, &coins(3, ATOM)).unwrap();
+
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert!(!resp.is_open);
+    assert_eq!(resp.cancellation, Some(Cancellation {
+        reason: Some("Withdrawn".to_string()),
+        refund_commission: true,
+    }));
+
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.outcome, Outcome::Cancelled {});
+    assert_eq!(resp.winner, None);
+    assert_eq!(resp.proceeds, coin(0, ATOM));
+
+    let err = BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed {});
+
+    // The highest bidder is not a winner
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder2).unwrap();
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder1).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder1).unwrap(), coins(10, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&bidder2).unwrap(), coins(20, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn cancel_rejected_after_end_time() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            end_time: Some(now.plus_seconds(100)),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(20, ATOM)).unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    // Pausing keeps the auction from being closed, but not from ending
+    BiddingContract::pause(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    let err = BiddingContract::cancel(&contract, &mut app, AUCTION_ID, &owner, None, true, &coins(2, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed {});
+
+    BiddingContract::unpause(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.outcome, Outcome::Sold {});
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(20, ATOM));
+}
+
+#[test]
+fn cancel_refunds_escrowed_commission() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let bidder = Addr::unchecked("bidder");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            commission_policy: Some(CommissionPolicy::Escrowed {}),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    // Escrowed commission was never paid out, there is nothing to send along
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder, &coins(20, ATOM)).unwrap();
+    BiddingContract::cancel(&contract, &mut app, AUCTION_ID, &owner, None, false, &[]).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), vec![]);
+
+    let resp = BiddingContract::query_settlement(&contract, &app, AUCTION_ID).unwrap();
+    assert_eq!(resp.commission, coin(0, ATOM));
+
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder).unwrap(), coins(20, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn paused_auction_is_frozen() {
+    let sender = Addr::unchecked("sender");
+    let owner = Addr::unchecked("owner");
+    let pauser = Addr::unchecked("pauser");
+    let bidder1 = Addr::unchecked("bidder1");
+    let bidder2 = Addr::unchecked("bidder2");
+    let bidder3 = Addr::unchecked("bidder3");
+
+    // Setup inital funds
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bidder1, coins(10, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder2, coins(20, ATOM))
+            .unwrap();
+
+        router
+            .bank
+            .init_balance(storage, &bidder3, coins(30, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &sender,
+        "Bidding contract",
+        &owner,
+        &InstantiateMsg {
+            pauser: Some(pauser.to_string()),
+            ..instantiate_msg(&owner)
+        },
+    ).unwrap();
+
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder1, &coins(10, ATOM)).unwrap();
+
+    let err = BiddingContract::pause(&contract, &mut app, AUCTION_ID, &bidder1).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: owner.to_string() });
+
+    BiddingContract::pause(&contract, &mut app, AUCTION_ID, &pauser).unwrap();
+    let resp = BiddingContract::query_auction(&contract, &app, AUCTION_ID).unwrap();
+    assert!(resp.paused);
+    assert!(resp.is_open);
+    assert_eq!(resp.pauser, Some(pauser.to_string()));
+
+    let err = BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder2, &coins(20, ATOM)).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    let err = BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    BiddingContract::unpause(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    BiddingContract::bid(&contract, &mut app, AUCTION_ID, &bidder2, &coins(20, ATOM)).unwrap();
+    BiddingContract::close(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+
+    BiddingContract::pause(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    let err = BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder1).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    // Only the owner replaces the pauser
+    let err = BiddingContract::set_pauser(&contract, &mut app, AUCTION_ID, &pauser, None).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: owner.to_string() });
+
+    BiddingContract::set_pauser(&contract, &mut app, AUCTION_ID, &owner, None).unwrap();
+    let err = BiddingContract::unpause(&contract, &mut app, AUCTION_ID, &pauser).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized { owner: owner.to_string() });
+
+    BiddingContract::unpause(&contract, &mut app, AUCTION_ID, &owner).unwrap();
+    BiddingContract::retract(&contract, &mut app, AUCTION_ID, &bidder1).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bidder1).unwrap(), coins(9, ATOM));
+
+    // Sealed bids are not revealed while paused
+    let now = app.block_info().time;
+    let sealed_id = BiddingContract::create_auction(&contract, &mut app, &owner, InstantiateMsg {
+        end_time: Some(now.plus_seconds(100)),
+        mode: Some(AuctionMode::Sealed {
+            reveal_period: 100,
+            forfeit_unrevealed: false,
+        }),
+        ..instantiate_msg(&owner)
+    }).unwrap();
+
+    let commitment = sealed_bid_commitment(bidder3.as_str(), Uint128::new(20), "salt");
+    BiddingContract::commit(&contract, &mut app, sealed_id, &bidder3, commitment, &coins(30, ATOM)).unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    BiddingContract::pause(&contract, &mut app, sealed_id, &owner).unwrap();
+    let err = BiddingContract::reveal(&contract, &mut app, sealed_id, &bidder3, 20, "salt").unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    let resp = BiddingContract::query_highest_bid(&contract, &app, sealed_id).unwrap();
+    assert_eq!(resp.address, "");
+
+    BiddingContract::unpause(&contract, &mut app, sealed_id, &owner).unwrap();
+    BiddingContract::reveal(&contract, &mut app, sealed_id, &bidder3, 20, "salt").unwrap();
+
+    let resp = BiddingContract::query_highest_bid(&contract, &app, sealed_id).unwrap();
+    assert_eq!(resp.address, bidder3.as_str());
+}
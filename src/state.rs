@@ -1,17 +1,154 @@
-use cosmwasm_std::{Addr, Coin, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Timestamp, Uint128};
+use cw_utils::Expiration;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex};
 use serde::{Deserialize, Serialize};
 
-pub const OWNER: Item<Addr> = Item::new("owner");
-pub const COMMODITY: Item<String> = Item::new("Commodity");
-pub const BID_ASSET: Item<Coin> = Item::new("bid_asset");
-pub const COMMISSION: Item<Uint128> = Item::new("commission");
-pub const IS_OPEN: Item<bool> = Item::new("is_open");
-pub const BIDS: Map<Addr, Coin> = Map::new("bids");
-pub const HIGEST_BID: Item<Bid> = Item::new("highest_bid");
+use crate::msg::{AuctionMode, BidIncrement, Cancellation, Commission, CommissionPolicy, Nft, Outcome, SoftClose};
+
+pub const AUCTION_COUNT: Item<u64> = Item::new("auction_count");
+// Factory that instantiated the contract
+pub const REGISTRY: Item<Addr> = Item::new("registry");
+
+// Auction state keyed by auction id
+// Removed once ownership is renounced
+pub const OWNER: Map<u64, Addr> = Map::new("owner");
+pub const PENDING_OWNER: Map<u64, PendingOwner> = Map::new("pending_owner");
+pub const COMMODITY: Map<u64, String> = Map::new("Commodity");
+pub const NFT: Map<u64, Nft> = Map::new("nft");
+pub const MODE: Map<u64, AuctionMode> = Map::new("mode");
+pub const BID_ASSET: Map<u64, Coin> = Map::new("bid_asset");
+// Only set when bids are paid in a cw20 token
+pub const BID_TOKEN: Map<u64, Addr> = Map::new("bid_token");
+pub const HIDDEN_RESERVE: Map<u64, bool> = Map::new("hidden_reserve");
+pub const SECOND_PRICE: Map<u64, bool> = Map::new("second_price");
+pub const COMMISSION: Map<u64, Commission> = Map::new("commission");
+pub const COMMISSION_POLICY: Map<u64, CommissionPolicy> = Map::new("commission_policy");
+// Commission goes to the owner unless fee recipients are set
+pub const FEE_RECIPIENTS: Map<u64, Vec<FeeShare>> = Map::new("fee_recipients");
+pub const FEES: Map<(u64, Addr), Uint128> = Map::new("fees");
+pub const MIN_INCREMENT: Map<u64, BidIncrement> = Map::new("min_increment");
+pub const BUY_NOW: Map<u64, Uint128> = Map::new("buy_now");
+pub const IS_OPEN: Map<u64, bool> = Map::new("is_open");
+pub const PAUSED: Map<u64, bool> = Map::new("paused");
+pub const PAUSER: Map<u64, Addr> = Map::new("pauser");
+pub const SOLD: Map<u64, bool> = Map::new("sold");
+pub const START_TIME: Map<u64, Timestamp> = Map::new("start_time");
+pub const END_TIME: Map<u64, Timestamp> = Map::new("end_time");
+pub const SOFT_CLOSE: Map<u64, SoftClose> = Map::new("soft_close");
+pub const EXTENSIONS: Map<u64, u32> = Map::new("extensions");
+pub const BIDS: IndexedMap<(u64, Addr), Deposit, BidIndexes> = IndexedMap::new("bids", BidIndexes {
+    amount: MultiIndex::new(bid_amount, "bids", "bids__amount"),
+});
+// Number of bidders and the bid funds held for them, unrevealed deposits excluded
+pub const BID_COUNT: Map<u64, u64> = Map::new("bid_count");
+pub const ESCROWED: Map<u64, Uint128> = Map::new("escrowed");
+// Amount of the bid already retracted
+pub const CLAIMED: Map<(u64, Addr), Uint128> = Map::new("claimed");
+// Every bid in the order placed, numbered per auction
+pub const HISTORY: Map<(u64, u64), Addr> = Map::new("history");
+pub const BID_HISTORY: Map<(u64, Addr, u64), BidEvent> = Map::new("bid_history");
+pub const HIGEST_BID: Map<u64, Bid> = Map::new("highest_bid");
+// Hidden maximum of the highest bidder when bidding by proxy
+pub const PROXY_MAX: Map<u64, Uint128> = Map::new("proxy_max");
+// Commission of the proxy or second-price leader, held until the price it pays is known
+pub const HELD_COMMISSION: Map<u64, Uint128> = Map::new("held_commission");
+pub const RUNNER_UP: Map<u64, Bid> = Map::new("runner_up");
+pub const CLEARING_PRICE: Map<u64, Uint128> = Map::new("clearing_price");
+pub const SETTLEMENT: Map<u64, Settlement> = Map::new("settlement");
+pub const CANCELLATION: Map<u64, Cancellation> = Map::new("cancellation");
+pub const COMMITMENTS: Map<(u64, Addr), Commitment> = Map::new("commitments");
+pub const UNREVEALED: Map<u64, Uint128> = Map::new("unrevealed");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Bid {
     pub address: Addr,
     pub bid: Coin,
+}
+
+/// Owner proposed by the current owner, taking over once accepted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingOwner {
+    pub address: Addr,
+    pub expiry: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeeShare {
+    pub address: Addr,
+    pub share: Decimal,
+}
+
+/// Funds deposited by a bidder, the gross amount ranks the bid.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Deposit {
+    pub gross: Coin,
+    pub commission: Uint128,
+    /// Escrowed after commission, only the retractable surplus remains once the bidder wins.
+    pub net: Uint128,
+}
+
+/// Outcome of a closed auction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Settlement {
+    pub winner: Option<Addr>,
+    pub clearing_price: Uint128,
+    /// Commission paid to the fee recipients over the whole auction.
+    pub commission: Uint128,
+    /// Paid to the owner on close, the winning bid and any forfeited deposits.
+    pub proceeds: Uint128,
+    pub height: u64,
+    pub time: Timestamp,
+    pub outcome: Outcome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BidEvent {
+    pub height: u64,
+    pub time: Timestamp,
+    pub gross: Coin,
+    pub commission: Uint128,
+    /// Gross amount of the bidder after the bid.
+    pub total: Coin,
+}
+
+pub struct BidIndexes<'a> {
+    /// Bids of an auction ordered by gross amount.
+    pub amount: MultiIndex<'a, (u64, u128), Deposit, (u64, Addr)>,
+}
+
+impl<'a> IndexList<Deposit> for BidIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Deposit>> + '_> {
+        let v: Vec<&dyn Index<Deposit>> = vec![&self.amount];
+        Box::new(v.into_iter())
+    }
+}
+
+fn bid_amount(pk: &[u8], deposit: &Deposit) -> (u64, u128) {
+    let (auction_id, _) = <(u64, Addr)>::from_slice(pk).expect("invalid bid key");
+
+    (auction_id, deposit.gross.amount.u128())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Commitment {
+    pub commitment: Binary,
+    pub deposit: Coin,
+}
+
+/// Single auction layout of 0.1.0, only read when migrating.
+pub mod legacy {
+    use cosmwasm_std::{Addr, Coin, Uint128};
+    use cw_storage_plus::{Item, Map};
+
+    use super::Bid;
+
+    pub const OWNER: Item<Addr> = Item::new("owner");
+    pub const COMMODITY: Item<String> = Item::new("Commodity");
+    pub const BID_ASSET: Item<Coin> = Item::new("bid_asset");
+    // Whole-number percentage of each bid
+    pub const COMMISSION: Item<Uint128> = Item::new("commission");
+    pub const IS_OPEN: Item<bool> = Item::new("is_open");
+    // Cumulative bid after commission
+    pub const BIDS: Map<Addr, Coin> = Map::new("bids");
+    pub const HIGEST_BID: Item<Bid> = Item::new("highest_bid");
 }
\ No newline at end of file